@@ -0,0 +1,71 @@
+use advent_of_code::generate::{self, Rng};
+use advent_of_code::parens;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// The size of the generated input, much larger than a real puzzle input.
+const INPUT_SIZE: usize = 4 * 1024 * 1024;
+
+/// Follow each direction one character at a time, like the original solution.
+fn char_final_floor(directions: &str) -> i64 {
+    directions
+        .chars()
+        .map(|c| match c {
+            '(' => 1,
+            ')' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Find the first position on a floor one character at a time, like the
+/// original solution.
+fn char_first_visit(directions: &str, target: i64) -> Option<usize> {
+    let mut floor = 0;
+
+    for (position, c) in directions.chars().enumerate() {
+        match c {
+            '(' => floor += 1,
+            ')' => floor -= 1,
+            _ => continue,
+        }
+
+        if floor == target {
+            return Some(position + 1);
+        }
+    }
+
+    None
+}
+
+fn bench_parens(c: &mut Criterion) {
+    let directions = generate::parens(&mut Rng::new(2015), INPUT_SIZE);
+    let directions = directions.as_str();
+    let bytes = directions.as_bytes();
+
+    // Make sure both approaches agree before comparing them.
+    assert_eq!(parens::final_floor(bytes), char_final_floor(directions));
+    let basement = parens::first_visit(bytes, -1);
+    assert!(basement.is_some());
+    assert_eq!(basement, char_first_visit(directions, -1));
+
+    let mut group = c.benchmark_group("final_floor");
+    group.bench_function("bytes", |b| {
+        b.iter(|| parens::final_floor(black_box(bytes)))
+    });
+    group.bench_function("chars", |b| {
+        b.iter(|| char_final_floor(black_box(directions)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("first_visit");
+    group.bench_function("bytes", |b| {
+        b.iter(|| parens::first_visit(black_box(bytes), -1))
+    });
+    group.bench_function("chars", |b| {
+        b.iter(|| char_first_visit(black_box(directions), -1))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parens);
+criterion_main!(benches);
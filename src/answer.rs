@@ -0,0 +1,293 @@
+use num_bigint::BigInt;
+
+/// Integers that can be used in overflow checked calculations.
+pub trait Checked: Copy + Into<BigInt> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn wrapping_mul(self, other: Self) -> Self;
+
+    /// Get the value as an `i64`, if it fits.
+    fn to_i64(self) -> Option<i64>;
+}
+
+macro_rules! impl_checked {
+    ($($t:ty),*) => {
+        $(
+            impl Checked for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+
+                fn wrapping_add(self, other: Self) -> Self {
+                    <$t>::wrapping_add(self, other)
+                }
+
+                fn wrapping_sub(self, other: Self) -> Self {
+                    <$t>::wrapping_sub(self, other)
+                }
+
+                fn wrapping_mul(self, other: Self) -> Self {
+                    <$t>::wrapping_mul(self, other)
+                }
+
+                fn to_i64(self) -> Option<i64> {
+                    std::convert::TryFrom::try_from(self).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(i32, i64, u32, u64, u128, usize);
+
+/// Report an overflow in a calculation.
+fn overflow(day: &str, operation: &str) -> ! {
+    panic!("{}: overflow while {}", day, operation)
+}
+
+/// Apply a checked operation in debug builds, reporting the day and operation
+/// if it overflowed, or the wrapping operation in release builds, matching how
+/// normal arithmetic behaves.
+fn apply<T>(
+    day: &str,
+    operation: &str,
+    checked: impl FnOnce() -> Option<T>,
+    wrapping: impl FnOnce() -> T,
+) -> T {
+    if cfg!(debug_assertions) {
+        checked().unwrap_or_else(|| overflow(day, operation))
+    } else {
+        wrapping()
+    }
+}
+
+/// Add two numbers as part of an operation for a day.
+pub fn add<T: Checked>(day: &str, operation: &str, a: T, b: T) -> T {
+    apply(day, operation, || a.checked_add(b), || a.wrapping_add(b))
+}
+
+/// Subtract a number from another as part of an operation for a day.
+pub fn sub<T: Checked>(day: &str, operation: &str, a: T, b: T) -> T {
+    apply(day, operation, || a.checked_sub(b), || a.wrapping_sub(b))
+}
+
+/// Multiply two numbers as part of an operation for a day.
+pub fn mul<T: Checked>(day: &str, operation: &str, a: T, b: T) -> T {
+    apply(day, operation, || a.checked_mul(b), || a.wrapping_mul(b))
+}
+
+/// Add every number as part of an operation for a day.
+pub fn sum<T: Checked>(day: &str, operation: &str, values: impl IntoIterator<Item = T>) -> T {
+    values
+        .into_iter()
+        .fold(T::ZERO, |acc, value| add(day, operation, acc, value))
+}
+
+/// Multiply every number as part of an operation for a day.
+pub fn product<T: Checked>(day: &str, operation: &str, values: impl IntoIterator<Item = T>) -> T {
+    values
+        .into_iter()
+        .fold(T::ONE, |acc, value| mul(day, operation, acc, value))
+}
+
+/// An answer to a puzzle. Answers are kept as an `i64` and only promoted to a
+/// `BigInt` once they would overflow, so they are always correct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Small(i64),
+    Big(BigInt),
+}
+
+impl Answer {
+    /// Get the answer as an `i64`, if it fits.
+    pub fn to_i64(&self) -> Option<i64> {
+        match self {
+            Answer::Small(value) => Some(*value),
+            Answer::Big(_) => None,
+        }
+    }
+
+    /// Get the answer as a `BigInt`.
+    pub fn to_bigint(&self) -> BigInt {
+        match self {
+            Answer::Small(value) => BigInt::from(*value),
+            Answer::Big(value) => value.clone(),
+        }
+    }
+
+    /// Add every value without overflowing.
+    pub fn sum<T: Checked>(values: impl IntoIterator<Item = T>) -> Self {
+        values
+            .into_iter()
+            .fold(Answer::Small(0), |acc, value| acc + Answer::from(value))
+    }
+
+    /// Multiply every value without overflowing.
+    pub fn product<T: Checked>(values: impl IntoIterator<Item = T>) -> Self {
+        values
+            .into_iter()
+            .fold(Answer::Small(1), |acc, value| acc * Answer::from(value))
+    }
+
+    /// Combine two answers, staying small if the checked operation succeeds
+    /// and otherwise falling back to big integers.
+    fn combine(
+        self,
+        other: Self,
+        checked: impl FnOnce(i64, i64) -> Option<i64>,
+        big: impl FnOnce(BigInt, BigInt) -> BigInt,
+    ) -> Self {
+        if let (Answer::Small(a), Answer::Small(b)) = (&self, &other) {
+            if let Some(value) = checked(*a, *b) {
+                return Answer::Small(value);
+            }
+        }
+
+        Answer::Big(big(self.to_bigint(), other.to_bigint()))
+    }
+}
+
+impl<T: Checked> From<T> for Answer {
+    fn from(value: T) -> Self {
+        match value.to_i64() {
+            Some(value) => Answer::Small(value),
+            None => Answer::Big(value.into()),
+        }
+    }
+}
+
+impl std::ops::Add for Answer {
+    type Output = Answer;
+
+    fn add(self, other: Answer) -> Answer {
+        self.combine(other, i64::checked_add, |a, b| a + b)
+    }
+}
+
+impl std::ops::AddAssign<&Answer> for Answer {
+    fn add_assign(&mut self, other: &Answer) {
+        let answer = std::mem::replace(self, Answer::Small(0));
+        *self = answer.combine(other.clone(), i64::checked_add, |a, b| a + b);
+    }
+}
+
+impl std::ops::Mul for Answer {
+    type Output = Answer;
+
+    fn mul(self, other: Answer) -> Answer {
+        self.combine(other, i64::checked_mul, |a, b| a * b)
+    }
+}
+
+impl serde::Serialize for Answer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Big answers are written as strings, as most consumers of formats like
+        // JSON can't represent them as numbers.
+        match self {
+            Answer::Small(value) => serializer.serialize_i64(*value),
+            Answer::Big(value) => serializer.serialize_str(&value.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Small(value) => write!(f, "{}", value),
+            Answer::Big(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked() {
+        assert_eq!(add("test", "adding", 2u32, 3), 5);
+        assert_eq!(sub("test", "subtracting", 2i32, 3), -1);
+        assert_eq!(mul("test", "multiplying", 4usize, 5), 20);
+        assert_eq!(sum("test", "summing", vec![1u64, 2, 3]), 6);
+        assert_eq!(product("test", "multiplying", vec![2i64, 3, 4]), 24);
+        assert_eq!(product("test", "multiplying", Vec::<u32>::new()), 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "2020-01: overflow while multiplying entries")]
+    fn test_checked_overflow() {
+        product("2020-01", "multiplying entries", vec![i32::MAX, 2]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "2015-02: overflow while subtracting")]
+    fn test_checked_underflow() {
+        sub("2015-02", "subtracting", 1u64, 2);
+    }
+
+    #[test]
+    fn test_answer_from() {
+        assert_eq!(Answer::from(5u32), Answer::Small(5));
+        assert_eq!(Answer::from(-5i32), Answer::Small(-5));
+        assert_eq!(Answer::from(u64::MAX), Answer::Big(BigInt::from(u64::MAX)));
+        assert_eq!(Answer::from(u64::MAX).to_i64(), None);
+        assert_eq!(Answer::from(u64::MAX).to_string(), u64::MAX.to_string());
+    }
+
+    #[test]
+    fn test_answer_promotes() {
+        assert_eq!(Answer::sum(vec![1i32, 2, 3]), Answer::Small(6));
+        assert_eq!(Answer::product(vec![2u64, 3, 4]), Answer::Small(24));
+
+        let answer = Answer::product(vec![i32::MAX; 3]);
+        assert_eq!(answer.to_bigint(), BigInt::from(i32::MAX).pow(3));
+        assert_eq!(answer.to_i64(), None);
+
+        let answer = Answer::sum(vec![i64::MAX, 1]);
+        assert_eq!(answer.to_string(), "9223372036854775808");
+
+        let answer = Answer::product(vec![u64::MAX, 0]);
+        assert_eq!(answer.to_bigint(), BigInt::from(0));
+        assert_eq!(Answer::product(vec![-2i64, i64::MAX]).to_i64(), None);
+    }
+
+    #[test]
+    fn test_answer_serialize() {
+        assert_eq!(serde_json::to_string(&Answer::Small(5)).unwrap(), "5");
+        assert_eq!(
+            serde_json::to_string(&Answer::from(u64::MAX)).unwrap(),
+            format!("\"{}\"", u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_answer_add_assign() {
+        let mut answer = Answer::Small(1);
+        answer += &Answer::Small(2);
+        assert_eq!(answer, Answer::Small(3));
+
+        let mut answer = Answer::Small(i64::MAX);
+        answer += &Answer::Small(1);
+        assert_eq!(answer, Answer::Big(BigInt::from(i64::MAX) + 1));
+        assert_eq!(answer.to_i64(), None);
+    }
+}
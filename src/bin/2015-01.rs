@@ -1,3 +1,7 @@
+use std::collections::BTreeMap;
+
+use advent_of_code::parens;
+
 const PROBLEM_NAME: &str = "2015-01";
 
 fn main() {
@@ -5,42 +9,226 @@ fn main() {
 
     let input = advent_of_code::load_input(PROBLEM_NAME);
 
-    let floors = process_floors(&input);
-    log::info!("Part 1 = {}", floors);
+    // Details about any floor can be requested with the first argument.
+    let requested_floor: Option<i32> = advent_of_code::utils::arg(0);
+
+    // Counting bytes is much faster for large inputs, but the full trace is
+    // needed to report unknown characters or to show more details.
+    let directions = input.as_bytes();
+    let only_parens = directions.iter().all(|b| *b == b'(' || *b == b')');
+    if only_parens && requested_floor.is_none() && !log::log_enabled!(log::Level::Debug) {
+        log::info!("Part 1 = {}", parens::final_floor(directions));
+
+        let stops = parens::first_visit(directions, -1).expect("never reached floor");
+        log::info!("Part 2 = {}", stops);
+
+        return;
+    }
+
+    let trace = FloorTrace::new(&input);
+
+    for unknown in trace.unknown() {
+        log::warn!(
+            "Skipped unknown character {:?} at position {}",
+            unknown.character,
+            unknown.position
+        );
+    }
+
+    log::info!("Part 1 = {}", trace.final_floor());
 
-    let stops = stops_before_floor(&input, -1);
+    let stops = trace.first_visit(-1).expect("never reached floor");
     log::info!("Part 2 = {}", stops);
+
+    log::debug!(
+        "Made {} moves between floors {} and {}",
+        trace.timeline().len() - 1,
+        trace.min_floor(),
+        trace.max_floor()
+    );
+    for (floor, visits) in trace.floors() {
+        log::trace!(
+            "Floor {} first visited at {}, last at {}, for {} steps",
+            floor,
+            visits.first,
+            visits.last,
+            visits.time
+        );
+    }
+
+    if let Some(floor) = requested_floor {
+        match (trace.first_visit(floor), trace.last_visit(floor)) {
+            (Some(first), Some(last)) => {
+                log::info!(
+                    "Floor {} first visited at {}, last at {}",
+                    floor,
+                    first,
+                    last
+                )
+            }
+            _ => log::info!("Floor {} was never visited", floor),
+        }
+    }
 }
 
-/// Decode a parenthesis into ascending (+1) or decending (-1).
-///
-/// # Panics
-///
-/// Will cause a panic if the passed character is not a parenthesis.
-fn decode_paren(paren: char) -> i32 {
+/// Decode a parenthesis into ascending (+1) or decending (-1), or `None` if the
+/// character is not a parenthesis.
+fn decode_paren(paren: char) -> Option<i32> {
     match paren {
-        '(' => 1,
-        ')' => -1,
-        _ => panic!("unexpected symbol"),
+        '(' => Some(1),
+        ')' => Some(-1),
+        _ => None,
+    }
+}
+
+/// A character in the input that wasn't a parenthesis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct UnknownChar {
+    /// The 1-indexed position of the character.
+    position: usize,
+    character: char,
+}
+
+/// When a floor was visited, in positions of the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FloorVisits {
+    /// The position where the floor was first reached, where 0 is the start.
+    first: usize,
+    /// The position where the floor was last reached.
+    last: usize,
+    /// The number of steps spent on the floor, including the start.
+    time: usize,
+}
+
+/// Every floor visited while following the directions, built in a single pass.
+///
+/// Positions are 1-indexed positions in the input, with position 0 being the
+/// start on floor 0. Unknown characters are skipped without moving but still
+/// take up a position.
+#[derive(Clone, Debug)]
+struct FloorTrace {
+    timeline: Vec<(usize, i32)>,
+    visits: BTreeMap<i32, FloorVisits>,
+    unknown: Vec<UnknownChar>,
+}
+
+impl FloorTrace {
+    /// Follow every direction in the input.
+    fn new(input: &str) -> Self {
+        let mut trace = Self {
+            timeline: vec![(0, 0)],
+            visits: BTreeMap::new(),
+            unknown: Vec::new(),
+        };
+        trace.visit(0, 0);
+
+        let mut floor = 0;
+
+        for (index, c) in input.chars().enumerate() {
+            let position = index + 1;
+
+            let change = match decode_paren(c) {
+                Some(change) => change,
+                None => {
+                    trace.unknown.push(UnknownChar {
+                        position,
+                        character: c,
+                    });
+                    continue;
+                }
+            };
+
+            floor += change;
+            log::trace!("Position {} moved to floor {}", position, floor);
+
+            trace.timeline.push((position, floor));
+            trace.visit(position, floor);
+        }
+
+        trace
+    }
+
+    /// Record arriving on a floor at a position.
+    fn visit(&mut self, position: usize, floor: i32) {
+        let visits = self.visits.entry(floor).or_insert(FloorVisits {
+            first: position,
+            last: position,
+            time: 0,
+        });
+
+        visits.last = position;
+        visits.time += 1;
+    }
+
+    /// Each position that moved and the floor it moved to, starting with
+    /// floor 0 at position 0.
+    fn timeline(&self) -> &[(usize, i32)] {
+        &self.timeline
+    }
+
+    /// Every character that wasn't a parenthesis.
+    fn unknown(&self) -> &[UnknownChar] {
+        &self.unknown
+    }
+
+    /// The floor after following every direction.
+    fn final_floor(&self) -> i32 {
+        self.timeline
+            .last()
+            .map(|(_position, floor)| *floor)
+            .unwrap_or(0)
+    }
+
+    /// The lowest floor reached.
+    fn min_floor(&self) -> i32 {
+        *self.visits.keys().next().unwrap()
+    }
+
+    /// The highest floor reached.
+    fn max_floor(&self) -> i32 {
+        *self.visits.keys().next_back().unwrap()
+    }
+
+    /// When a floor was visited, if it ever was.
+    fn visits(&self, floor: i32) -> Option<FloorVisits> {
+        self.visits.get(&floor).copied()
+    }
+
+    /// The position where a floor was first reached.
+    fn first_visit(&self, floor: i32) -> Option<usize> {
+        self.visits(floor).map(|visits| visits.first)
+    }
+
+    /// The position where a floor was last reached.
+    fn last_visit(&self, floor: i32) -> Option<usize> {
+        self.visits(floor).map(|visits| visits.last)
+    }
+
+    /// Every visited floor from lowest to highest.
+    fn floors(&self) -> impl Iterator<Item = (i32, FloorVisits)> + '_ {
+        self.visits.iter().map(|(floor, visits)| (*floor, *visits))
     }
 }
 
-/// Iterate through input to sum floors.
+/// Iterate through input to sum floors. Used to check [`FloorTrace`].
+#[cfg(test)]
 fn process_floors(input: &str) -> i32 {
-    input.chars().into_iter().map(decode_paren).sum()
+    input.chars().filter_map(decode_paren).sum()
 }
 
-/// Calculate the number of parentheses before reaching a desired floor.
+/// Calculate the number of parentheses before reaching a desired floor. Used to
+/// check [`FloorTrace`].
 ///
 /// # Panics
 ///
 /// Will panic if the floor is never reached.
+#[cfg(test)]
 fn stops_before_floor(input: &str, desired: i32) -> i32 {
     let mut floor = 0;
 
     // Iterate through each parenthesis, taking note of the position.
-    for (position, paren) in input.chars().into_iter().enumerate() {
-        floor += decode_paren(paren);
+    for (position, paren) in input.chars().enumerate() {
+        floor += decode_paren(paren).unwrap_or(0);
 
         log::trace!("Position {} moved to floor {}", position, floor);
 
@@ -60,8 +248,9 @@ mod tests {
 
     #[test]
     fn test_decode_paren() {
-        assert_eq!(decode_paren('('), 1);
-        assert_eq!(decode_paren(')'), -1);
+        assert_eq!(decode_paren('('), Some(1));
+        assert_eq!(decode_paren(')'), Some(-1));
+        assert_eq!(decode_paren('x'), None);
     }
 
     #[test]
@@ -84,4 +273,118 @@ mod tests {
         let stops = stops_before_floor("()()(()", 2);
         assert_eq!(stops, 6);
     }
+
+    #[test]
+    fn test_floor_trace() {
+        advent_of_code::init();
+
+        let trace = FloorTrace::new("(()))(");
+        assert_eq!(
+            trace.timeline(),
+            &[(0, 0), (1, 1), (2, 2), (3, 1), (4, 0), (5, -1), (6, 0)]
+        );
+        assert_eq!(trace.final_floor(), 0);
+        assert_eq!(trace.min_floor(), -1);
+        assert_eq!(trace.max_floor(), 2);
+
+        assert_eq!(
+            trace.visits(0),
+            Some(FloorVisits {
+                first: 0,
+                last: 6,
+                time: 3
+            })
+        );
+        assert_eq!(trace.first_visit(1), Some(1));
+        assert_eq!(trace.last_visit(1), Some(3));
+        assert_eq!(trace.first_visit(-1), Some(5));
+        assert_eq!(trace.first_visit(3), None);
+
+        let floors: Vec<_> = trace.floors().map(|(floor, _visits)| floor).collect();
+        assert_eq!(floors, vec![-1, 0, 1, 2]);
+        assert!(trace.unknown().is_empty());
+    }
+
+    #[test]
+    fn test_floor_trace_empty() {
+        advent_of_code::init();
+
+        let trace = FloorTrace::new("");
+        assert_eq!(trace.final_floor(), 0);
+        assert_eq!((trace.min_floor(), trace.max_floor()), (0, 0));
+        assert_eq!(trace.first_visit(0), Some(0));
+    }
+
+    #[test]
+    fn test_floor_trace_unknown() {
+        advent_of_code::init();
+
+        let trace = FloorTrace::new("(x)é)");
+        assert_eq!(
+            trace.unknown(),
+            &[
+                UnknownChar {
+                    position: 2,
+                    character: 'x'
+                },
+                UnknownChar {
+                    position: 4,
+                    character: 'é'
+                },
+            ]
+        );
+
+        // Unknown characters still take up a position.
+        assert_eq!(trace.final_floor(), -1);
+        assert_eq!(trace.first_visit(-1), Some(5));
+        assert_eq!(
+            trace.first_visit(-1),
+            Some(stops_before_floor("(x)é)", -1) as usize)
+        );
+    }
+
+    #[test]
+    fn test_parens_matches_original() {
+        advent_of_code::init();
+
+        for input in &["((()", "))", ")", "()())", "()()(()", "(()(()(", ")())())"] {
+            assert_eq!(
+                parens::final_floor(input.as_bytes()),
+                process_floors(input) as i64
+            );
+
+            if let Some(stops) = parens::first_visit(input.as_bytes(), -1) {
+                assert_eq!(stops as i32, stops_before_floor(input, -1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_floor_trace_matches_original() {
+        advent_of_code::init();
+
+        for input in &["((()", "))", ")", "()())", "()()(()", "(()(()(", ")())())"] {
+            let trace = FloorTrace::new(input);
+            assert_eq!(trace.final_floor(), process_floors(input));
+
+            if let Some(stops) = trace.first_visit(-1) {
+                assert_eq!(stops as i32, stops_before_floor(input, -1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_input() {
+        advent_of_code::init();
+
+        for input in advent_of_code::generate::inputs(PROBLEM_NAME, 5, 2000) {
+            let trace = FloorTrace::new(&input);
+            assert!(trace.unknown().is_empty());
+            assert_eq!(trace.final_floor(), process_floors(&input));
+            assert_eq!(
+                trace.first_visit(-1),
+                Some(stops_before_floor(&input, -1) as usize)
+            );
+        }
+    }
 }
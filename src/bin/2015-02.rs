@@ -1,111 +1,375 @@
+use advent_of_code::answer::{self, Answer};
+use serde::Serialize;
+
 const PROBLEM_NAME: &str = "2015-02";
 
-/// A package's three dimensions.
+/// The number of buckets in the histogram of paper per package.
+const HISTOGRAM_BUCKETS: u128 = 10;
+
+/// A box with any number of dimensions. Packages from the puzzle have three,
+/// a length, width, and height.
+///
+/// Measurements are calculated as `u128`, which can't overflow for packages
+/// with up to 4 dimensions.
 #[derive(Clone, Debug, PartialEq)]
-struct Package {
-    length: i32,
-    width: i32,
-    height: i32,
+struct Package<const N: usize> {
+    dimensions: [u32; N],
 }
 
 #[cfg(test)]
-impl Package {
-    fn new(length: i32, width: i32, height: i32) -> Self {
-        Self {
-            length,
-            width,
-            height,
-        }
+impl<const N: usize> Package<N> {
+    fn new(dimensions: [u32; N]) -> Self {
+        Self { dimensions }
     }
 }
 
-impl Package {
-    /// Calculate the amount of wrapping paper required for the package.
-    fn wrapping_paper(&self) -> i32 {
-        // Calculate base area of the paper needed.
-        let area = (2 * self.length * self.width)
-            + (2 * self.width * self.height)
-            + (2 * self.height * self.length);
+impl<const N: usize> Package<N> {
+    /// The dimensions from smallest to largest.
+    fn sorted(&self) -> [u32; N] {
+        let mut dimensions = self.dimensions;
+        dimensions.sort_unstable();
+
+        dimensions
+    }
 
-        // Calculate each side to find the side with the least area.
-        let smallest = vec![
-            self.length * self.width,
-            self.width * self.height,
-            self.height * self.length,
-        ]
-        .into_iter()
-        .min()
-        .unwrap();
+    /// The product of every dimension.
+    fn volume(&self) -> u128 {
+        answer::product(
+            PROBLEM_NAME,
+            "calculating volume",
+            self.dimensions.iter().map(|d| u128::from(*d)),
+        )
+    }
+
+    /// The area of each distinct face, which is the product of every dimension
+    /// except one.
+    fn faces(&self) -> impl Iterator<Item = u128> + '_ {
+        (0..N).map(move |skip| {
+            answer::product(
+                PROBLEM_NAME,
+                "calculating face area",
+                self.dimensions
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _dimension)| *index != skip)
+                    .map(|(_index, dimension)| u128::from(*dimension)),
+            )
+        })
+    }
+
+    /// The total area of every face. Each distinct face appears twice, on
+    /// opposite sides of the box.
+    fn surface_area(&self) -> u128 {
+        let faces = answer::sum(PROBLEM_NAME, "calculating surface area", self.faces());
+        answer::mul(PROBLEM_NAME, "calculating surface area", 2, faces)
+    }
+
+    /// The area of the smallest face, made from every dimension except the
+    /// largest.
+    fn smallest_face(&self) -> u128 {
+        answer::product(
+            PROBLEM_NAME,
+            "calculating smallest face",
+            self.sorted()
+                .iter()
+                .take(N.saturating_sub(1))
+                .map(|d| u128::from(*d)),
+        )
+    }
 
-        area + smallest
+    /// The distance around the smallest face, which is twice every dimension
+    /// except the largest.
+    fn smallest_perimeter(&self) -> u128 {
+        answer::sum(
+            PROBLEM_NAME,
+            "calculating smallest perimeter",
+            self.sorted()
+                .iter()
+                .take(N.saturating_sub(1))
+                .map(|d| 2 * u128::from(*d)),
+        )
+    }
+
+    /// Calculate the amount of wrapping paper required for the package.
+    fn wrapping_paper(&self) -> u128 {
+        // The whole surface, plus a little extra for the smallest face.
+        answer::add(
+            PROBLEM_NAME,
+            "calculating wrapping paper",
+            self.surface_area(),
+            self.smallest_face(),
+        )
     }
 
     /// Calculate the amount of ribbon needed for the package.
-    fn ribbon(&self) -> i32 {
-        // Sort the dimensions to find the two smallest edges.
-        let mut faces = vec![self.length, self.width, self.height];
-        faces.sort();
+    fn ribbon(&self) -> u128 {
+        // Enough to wrap around the smallest face, plus a bow as long as the
+        // volume.
+        answer::add(
+            PROBLEM_NAME,
+            "calculating ribbon",
+            self.smallest_perimeter(),
+            self.volume(),
+        )
+    }
+}
+
+impl<const N: usize> std::str::FromStr for Package<N> {
+    type Err = &'static str;
+
+    /// Parse a package from dimensions separated by an 'x', such as `2x3x4`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut dimensions = [0; N];
+        let mut parts = s.trim().split('x');
+
+        for dimension in dimensions.iter_mut() {
+            let part = parts.next().ok_or("package has too few dimensions")?;
 
-        // These will always have 3 values.
-        let ribbon = faces[0] * 2 + faces[1] * 2;
-        let bow: i32 = faces.iter().product();
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err("package dimensions must be numbers");
+            }
+
+            *dimension = part.parse().map_err(|_| "package dimension is too large")?;
+        }
+
+        if parts.next().is_some() {
+            return Err("package has too many dimensions");
+        }
+
+        Ok(Self { dimensions })
+    }
+}
 
-        ribbon + bow
+impl<const N: usize> std::fmt::Display for Package<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dimensions: Vec<_> = self.dimensions.iter().map(u32::to_string).collect();
+        write!(f, "{}", dimensions.join("x"))
+    }
+}
+
+/// Ways an order summary can be displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SummaryFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for SummaryFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(SummaryFormat::Text),
+            "json" => Ok(SummaryFormat::Json),
+            _ => Err("summary format must be text or json"),
+        }
     }
 }
 
 fn main() {
     advent_of_code::init();
 
+    // The number of dimensions of each package can be provided as the first
+    // argument, and a format for an order summary as the second.
+    let dimensions = advent_of_code::utils::arg(0).unwrap_or(3);
+    let format = advent_of_code::utils::arg::<SummaryFormat>(1);
+
     let input = advent_of_code::load_input(PROBLEM_NAME);
-    let packages = decode_all_packages(&input);
+
+    match dimensions {
+        2 => run::<2>(&input, format),
+        3 => run::<3>(&input, format),
+        4 => run::<4>(&input, format),
+        _ => {
+            log::error!("Packages must have 2, 3, or 4 dimensions");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Solve the puzzle for packages with `N` dimensions.
+fn run<const N: usize>(input: &str, format: Option<SummaryFormat>) {
+    let packages: Vec<Package<N>> = decode_all_packages(input).unwrap_or_else(|err| {
+        log::error!("Invalid package: {}", err);
+        std::process::exit(1);
+    });
 
     let total_paper = total_wrapping_paper(&packages);
     log::info!("Part 1 = {}", total_paper);
 
     let total_ribbon = total_ribbon(&packages);
     log::info!("Part 2 = {}", total_ribbon);
+
+    if let Some(format) = format {
+        let summary = OrderSummary::new(&packages);
+
+        match format {
+            SummaryFormat::Text => print!("{}", summary),
+            SummaryFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&summary).expect("summary should serialize")
+            ),
+        }
+    }
 }
 
-/// Decode each line into a package.
-fn decode_all_packages(input: &str) -> Vec<Package> {
-    input.lines().map(decode_package).collect()
+/// Decode each line into a package, skipping blank lines.
+fn decode_all_packages<const N: usize>(input: &str) -> Result<Vec<Package<N>>, String> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_index, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            line.parse()
+                .map_err(|err| format!("line {}: {}", index + 1, err))
+        })
+        .collect()
 }
 
 /// Calculate total amount of wrapping paper needed.
-fn total_wrapping_paper(packages: &[Package]) -> i32 {
-    packages.iter().map(Package::wrapping_paper).sum()
+fn total_wrapping_paper<const N: usize>(packages: &[Package<N>]) -> Answer {
+    Answer::sum(packages.iter().map(Package::wrapping_paper))
 }
 
 /// Calculate total amount of ribbon needed.
-fn total_ribbon(packages: &[Package]) -> i32 {
-    packages.iter().map(Package::ribbon).sum()
+fn total_ribbon<const N: usize>(packages: &[Package<N>]) -> Answer {
+    Answer::sum(packages.iter().map(Package::ribbon))
 }
 
-/// Decode a Package from a a length x width x height formatted line.
-///
-/// # Panics
-///
-/// Will panic if there are not 3 numeric dimensions separated by an 'x' on each
-/// line.
-fn decode_package(line: &str) -> Package {
-    let dimensions: Vec<&str> = line.split('x').collect();
-    assert_eq!(dimensions.len(), 3, "Package must have 3 dimensions");
-
-    let dimensions: Vec<i32> = dimensions
-        .into_iter()
-        .map(|dimension| {
-            dimension
-                .parse()
-                .expect("All package dimensions must be numbers")
+/// The package with the largest volume in an order.
+#[derive(Debug, PartialEq, Serialize)]
+struct LargestPackage {
+    /// The position of the package in the order, starting at 1.
+    package: usize,
+    dimensions: Vec<u32>,
+    volume: u128,
+}
+
+/// A range of amounts of paper, and how many packages needed that much.
+#[derive(Debug, PartialEq, Serialize)]
+struct Bucket {
+    /// The smallest amount of paper in the bucket.
+    start: u128,
+    /// The largest amount of paper in the bucket.
+    end: u128,
+    count: usize,
+}
+
+/// Totals and statistics for an entire order of packages.
+#[derive(Debug, Serialize)]
+struct OrderSummary {
+    packages: usize,
+    paper: Answer,
+    ribbon: Answer,
+    volume: Answer,
+    largest: Option<LargestPackage>,
+    paper_histogram: Vec<Bucket>,
+}
+
+impl OrderSummary {
+    fn new<const N: usize>(packages: &[Package<N>]) -> Self {
+        // Use the first package if several have the same volume.
+        let largest = packages
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_index, package)| package.volume())
+            .map(|(index, package)| LargestPackage {
+                package: index + 1,
+                dimensions: package.dimensions.to_vec(),
+                volume: package.volume(),
+            });
+
+        let paper: Vec<u128> = packages.iter().map(Package::wrapping_paper).collect();
+
+        Self {
+            packages: packages.len(),
+            paper: Answer::sum(paper.iter().copied()),
+            ribbon: total_ribbon(packages),
+            volume: Answer::sum(packages.iter().map(Package::volume)),
+            largest,
+            paper_histogram: histogram(&paper, HISTOGRAM_BUCKETS),
+        }
+    }
+}
+
+/// Split values into equally sized buckets between the smallest and largest
+/// value, counting how many values are in each.
+fn histogram(values: &[u128], buckets: u128) -> Vec<Bucket> {
+    let (min, max) = match (values.iter().min(), values.iter().max()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return Vec::new(),
+    };
+
+    let width = ((max - min) / buckets + 1).max(1);
+    let len = ((max - min) / width + 1) as usize;
+
+    let mut histogram: Vec<_> = (0..len as u128)
+        .map(|index| Bucket {
+            start: min + index * width,
+            end: min + (index + 1) * width - 1,
+            count: 0,
         })
         .collect();
 
-    // We know there are three dimensions in here because of previous assertion.
-    Package {
-        length: dimensions[0],
-        width: dimensions[1],
-        height: dimensions[2],
+    for value in values {
+        histogram[((value - min) / width) as usize].count += 1;
+    }
+
+    histogram
+}
+
+impl std::fmt::Display for OrderSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Packages: {}", self.packages)?;
+        writeln!(f, "Paper: {}", self.paper)?;
+        writeln!(f, "Ribbon: {}", self.ribbon)?;
+        writeln!(f, "Volume: {}", self.volume)?;
+
+        if let Some(largest) = &self.largest {
+            let dimensions: Vec<_> = largest.dimensions.iter().map(u32::to_string).collect();
+            writeln!(
+                f,
+                "Largest package: #{} ({}) with volume {}",
+                largest.package,
+                dimensions.join("x"),
+                largest.volume
+            )?;
+        }
+
+        if self.paper_histogram.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "Paper per package:")?;
+
+        // Scale the bars so the largest bucket fills the available width.
+        let most = self
+            .paper_histogram
+            .iter()
+            .map(|bucket| bucket.count)
+            .max()
+            .unwrap_or_default();
+        let label_width = self
+            .paper_histogram
+            .iter()
+            .map(|bucket| format!("{}-{}", bucket.start, bucket.end).len())
+            .max()
+            .unwrap_or_default();
+
+        for bucket in &self.paper_histogram {
+            let bar = "#".repeat((bucket.count * 40).div_ceil(most.max(1)));
+            writeln!(
+                f,
+                "  {:>width$} | {} {}",
+                format!("{}-{}", bucket.start, bucket.end),
+                bar,
+                bucket.count,
+                width = label_width
+            )?;
+        }
+
+        Ok(())
     }
 }
 
@@ -116,48 +380,186 @@ mod tests {
     #[test]
     fn test_decode_all_packages() {
         let input = "2x3x4\n1x1x10\n";
-        let packages = decode_all_packages(&input);
+        let packages = decode_all_packages(input).unwrap();
         assert_eq!(
             packages,
-            vec![Package::new(2, 3, 4), Package::new(1, 1, 10)]
+            vec![Package::new([2, 3, 4]), Package::new([1, 1, 10])]
+        );
+
+        assert_eq!(
+            decode_all_packages::<3>("2x3x4\n\n1x1\n").unwrap_err(),
+            "line 3: package has too few dimensions"
         );
     }
 
     #[test]
     fn test_total_wrapping_paper() {
-        let packages = &[Package::new(2, 3, 4), Package::new(1, 1, 10)];
+        let packages = &[Package::new([2, 3, 4]), Package::new([1, 1, 10])];
         let total_paper = total_wrapping_paper(packages);
-        assert_eq!(total_paper, 101);
+        assert_eq!(total_paper, Answer::Small(101));
+
+        // Totals too large for an `i64` are still correct.
+        let packages = vec![Package::new([1 << 30, 1 << 30, 1]); 4];
+        let total_paper = total_wrapping_paper(&packages);
+        assert_eq!(total_paper.to_i64(), None);
+        assert_eq!(total_paper.to_string(), "9223372058329612288");
+        assert_eq!(OrderSummary::new(&packages).paper, total_paper);
+    }
+
+    #[test]
+    fn test_largest_package() {
+        // The largest dimensions still give exact measurements.
+        let max = u128::from(u32::MAX);
+
+        let package = Package::new([u32::MAX; 3]);
+        assert_eq!(package.wrapping_paper(), 7 * max * max);
+        assert_eq!(package.ribbon(), 4 * max + max * max * max);
+        assert_eq!(
+            total_ribbon(&[package.clone(), package]).to_string(),
+            (2 * (4 * max + max * max * max)).to_string()
+        );
+
+        let tesseract = Package::new([u32::MAX; 4]);
+        assert_eq!(tesseract.volume(), max * max * max * max);
+        assert_eq!(tesseract.ribbon(), 6 * max + max * max * max * max);
     }
 
     #[test]
     fn test_total_ribbon() {
-        let packages = &[Package::new(2, 3, 4), Package::new(1, 1, 10)];
+        let packages = &[Package::new([2, 3, 4]), Package::new([1, 1, 10])];
         let total_ribbon = total_ribbon(packages);
-        assert_eq!(total_ribbon, 48);
+        assert_eq!(total_ribbon, Answer::Small(48));
     }
 
     #[test]
     fn test_decode_package() {
-        let package = decode_package("1x2x3");
-        assert_eq!(package, Package::new(1, 2, 3));
+        let package: Package<3> = "1x2x3".parse().unwrap();
+        assert_eq!(package, Package::new([1, 2, 3]));
+        assert_eq!(package.to_string(), "1x2x3");
+
+        let package: Package<4> = "1x2x3x4".parse().unwrap();
+        assert_eq!(package.dimensions, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_decode_package_errors() {
+        let errors = [
+            ("1x2", "package has too few dimensions"),
+            ("1x2x3x4", "package has too many dimensions"),
+            ("1xx3", "package dimensions must be numbers"),
+            ("1x-2x3", "package dimensions must be numbers"),
+            ("1x+2x3", "package dimensions must be numbers"),
+            ("1x2xa", "package dimensions must be numbers"),
+            ("1x2x99999999999", "package dimension is too large"),
+        ];
+
+        for (line, error) in &errors {
+            assert_eq!(line.parse::<Package<3>>().unwrap_err(), *error);
+        }
+    }
+
+    #[test]
+    fn test_package_geometry() {
+        let package = Package::new([3, 4, 2]);
+        assert_eq!(package.volume(), 24);
+        assert_eq!(package.surface_area(), 52);
+        assert_eq!(package.smallest_face(), 6);
+        assert_eq!(package.smallest_perimeter(), 10);
+
+        let square = Package::new([3, 4]);
+        assert_eq!(square.volume(), 12);
+        assert_eq!(square.surface_area(), 14);
+        assert_eq!(square.smallest_face(), 3);
+
+        let tesseract = Package::new([2, 2, 2, 2]);
+        assert_eq!(tesseract.volume(), 16);
+        assert_eq!(tesseract.surface_area(), 64);
+        assert_eq!(tesseract.smallest_face(), 8);
+        assert_eq!(tesseract.smallest_perimeter(), 12);
     }
 
     #[test]
     fn test_package_wrapping_paper() {
-        let paper = Package::new(2, 3, 4).wrapping_paper();
+        let paper = Package::new([2, 3, 4]).wrapping_paper();
         assert_eq!(paper, 58);
 
-        let paper = Package::new(1, 1, 10).wrapping_paper();
+        let paper = Package::new([1, 1, 10]).wrapping_paper();
         assert_eq!(paper, 43);
     }
 
     #[test]
     fn test_package_ribbon() {
-        let ribbon = Package::new(2, 3, 4).ribbon();
+        let ribbon = Package::new([2, 3, 4]).ribbon();
         assert_eq!(ribbon, 34);
 
-        let ribbon = Package::new(1, 1, 10).ribbon();
+        let ribbon = Package::new([1, 1, 10]).ribbon();
         assert_eq!(ribbon, 14);
     }
+
+    #[test]
+    fn test_histogram() {
+        assert!(histogram(&[], 10).is_empty());
+        assert_eq!(
+            histogram(&[5, 5], 10),
+            vec![Bucket {
+                start: 5,
+                end: 5,
+                count: 2
+            }]
+        );
+
+        let buckets = histogram(&[0, 9, 10, 19, 20, 25], 2);
+        let counts: Vec<_> = buckets
+            .iter()
+            .map(|bucket| (bucket.start, bucket.end, bucket.count))
+            .collect();
+        assert_eq!(counts, vec![(0, 12, 3), (13, 25, 3)]);
+    }
+
+    #[test]
+    fn test_order_summary() {
+        let packages = decode_all_packages::<3>("2x3x4\n1x1x10\n4x3x2").unwrap();
+        let summary = OrderSummary::new(&packages);
+
+        assert_eq!(summary.packages, 3);
+        assert_eq!(summary.paper, Answer::Small(159));
+        assert_eq!(summary.ribbon, Answer::Small(82));
+        assert_eq!(summary.volume, Answer::Small(58));
+        assert_eq!(
+            summary.largest,
+            Some(LargestPackage {
+                package: 1,
+                dimensions: vec![2, 3, 4],
+                volume: 24
+            })
+        );
+        let counted: usize = summary.paper_histogram.iter().map(|b| b.count).sum();
+        assert_eq!(counted, 3);
+
+        let text = summary.to_string();
+        assert!(text.contains("Largest package: #1 (2x3x4) with volume 24"));
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["paper"], 159);
+        assert_eq!(json["largest"]["dimensions"][2], 4);
+    }
+
+    #[test]
+    fn test_generated_input() {
+        advent_of_code::init();
+
+        for input in advent_of_code::generate::inputs(PROBLEM_NAME, 5, 100) {
+            let packages = decode_all_packages::<3>(&input).unwrap();
+
+            let summary = OrderSummary::new(&packages);
+            assert_eq!(summary.paper, total_wrapping_paper(&packages));
+            assert_eq!(summary.ribbon, total_ribbon(&packages));
+
+            let volumes: Vec<u128> = packages.iter().map(Package::volume).collect();
+            assert_eq!(
+                summary.volume,
+                Answer::Small(volumes.iter().sum::<u128>() as i64)
+            );
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 const PROBLEM_NAME: &str = "2015-03";
 
@@ -55,10 +55,220 @@ impl Direction {
     }
 }
 
+/// Which agent follows each direction when agents share a single stream of
+/// directions.
+#[derive(Clone, Debug, PartialEq)]
+enum Schedule {
+    /// Each agent takes a turn in order.
+    RoundRobin(usize),
+    /// Agents take turns following a repeating pattern of agent numbers.
+    Pattern(Vec<usize>),
+}
+
+impl Schedule {
+    /// The number of agents needed to follow this schedule.
+    fn agents(&self) -> usize {
+        match self {
+            Schedule::RoundRobin(agents) => *agents,
+            Schedule::Pattern(pattern) => pattern.iter().max().map_or(0, |max| max + 1),
+        }
+    }
+
+    /// Every agent that takes part in this schedule. Agents numbered below the
+    /// highest one in a pattern but missing from it never move.
+    fn members(&self) -> BTreeSet<usize> {
+        match self {
+            Schedule::RoundRobin(agents) => (0..*agents).collect(),
+            Schedule::Pattern(pattern) => pattern.iter().copied().collect(),
+        }
+    }
+
+    /// The agent who follows the direction at an index.
+    fn agent(&self, index: usize) -> usize {
+        match self {
+            Schedule::RoundRobin(agents) => index % agents,
+            Schedule::Pattern(pattern) => pattern[index % pattern.len()],
+        }
+    }
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = &'static str;
+
+    /// Parse a schedule from a number of agents taking turns, such as `2`, or a
+    /// comma separated pattern of agents, such as `0,0,1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(',') {
+            let pattern = s
+                .split(',')
+                .map(|agent| agent.trim().parse())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| "schedule pattern must be agent numbers")?;
+
+            return Ok(Schedule::Pattern(pattern));
+        }
+
+        match s.trim().parse() {
+            Ok(0) => Err("schedule must have at least one agent"),
+            Ok(agents) => Ok(Schedule::RoundRobin(agents)),
+            Err(_) => Err("schedule must be a number of agents or a pattern"),
+        }
+    }
+}
+
+/// Everything known about a single house.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct House {
+    /// The total number of visits from every agent.
+    visits: usize,
+    /// Each agent that visited the house.
+    agents: BTreeSet<usize>,
+}
+
+/// Agents moving around a grid of houses, delivering a present to each house
+/// they visit, including the one they start at.
+#[derive(Clone, Debug)]
+struct Simulation {
+    positions: Vec<Point>,
+    houses: HashMap<Point, House>,
+}
+
+impl Simulation {
+    /// Create a new simulation where every agent starts at the origin.
+    fn new(agents: usize) -> Self {
+        Self::with_members(agents, 0..agents)
+    }
+
+    /// Create a new simulation with room for a number of agents, where only
+    /// the members deliver a present at the origin.
+    fn with_members(agents: usize, members: impl IntoIterator<Item = usize>) -> Self {
+        let mut simulation = Self {
+            positions: vec![Point(0, 0); agents],
+            houses: HashMap::new(),
+        };
+
+        for agent in members {
+            simulation.visit(agent, Point(0, 0));
+        }
+
+        simulation
+    }
+
+    /// Run a simulation where agents share a stream of directions, taking
+    /// turns according to a schedule.
+    fn with_schedule(directions: &[Direction], schedule: &Schedule) -> Self {
+        let mut simulation = Self::with_members(schedule.agents(), schedule.members());
+
+        for (index, direction) in directions.iter().enumerate() {
+            simulation.step(schedule.agent(index), *direction);
+        }
+
+        simulation
+    }
+
+    /// Run a simulation where each agent has its own stream of directions.
+    fn with_streams(streams: &[Vec<Direction>]) -> Self {
+        let mut simulation = Self::new(streams.len());
+
+        for (agent, directions) in streams.iter().enumerate() {
+            for direction in directions {
+                simulation.step(agent, *direction);
+            }
+        }
+
+        simulation
+    }
+
+    /// Move an agent in a direction and visit the house it arrives at.
+    fn step(&mut self, agent: usize, direction: Direction) {
+        let point = &mut self.positions[agent];
+        *point += direction.movement();
+        log::trace!("Evaluating point {}, moved to {}", agent, point);
+
+        let point = *point;
+        self.visit(agent, point);
+    }
+
+    /// Record an agent visiting a house.
+    fn visit(&mut self, agent: usize, point: Point) {
+        let house = self.houses.entry(point).or_default();
+        house.visits += 1;
+        house.agents.insert(agent);
+    }
+
+    /// Every visited house.
+    fn houses(&self) -> impl Iterator<Item = (Point, &House)> {
+        self.houses.iter().map(|(point, house)| (*point, house))
+    }
+
+    /// Details about a single house, if it was visited.
+    fn house(&self, point: Point) -> Option<&House> {
+        self.houses.get(&point)
+    }
+
+    /// Every house visited by more than one agent, sorted by position.
+    fn shared_houses(&self) -> Vec<Point> {
+        let mut shared: Vec<_> = self
+            .houses()
+            .filter(|(_point, house)| house.agents.len() > 1)
+            .map(|(point, _house)| point)
+            .collect();
+        shared.sort_unstable_by_key(|point| (point.1, point.0));
+
+        shared
+    }
+
+    /// The smallest and largest corners of a box containing every visited
+    /// house.
+    fn bounding_box(&self) -> (Point, Point) {
+        self.houses
+            .keys()
+            .fold((Point(0, 0), Point(0, 0)), |(min, max), point| {
+                (
+                    Point(min.0.min(point.0), min.1.min(point.1)),
+                    Point(max.0.max(point.0), max.1.max(point.1)),
+                )
+            })
+    }
+
+    /// Draw every house within the bounding box, with `.` for houses that
+    /// weren't visited, the number of visits up to 9, and `+` for more.
+    fn render(&self) -> String {
+        let (min, max) = self.bounding_box();
+        let mut map = String::new();
+
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                let c = match self.house(Point(x, y)).map(|house| house.visits) {
+                    None => '.',
+                    Some(visits) if visits <= 9 => (b'0' + visits as u8) as char,
+                    Some(_) => '+',
+                };
+                map.push(c);
+            }
+            map.push('\n');
+        }
+
+        map
+    }
+}
+
 fn main() {
     advent_of_code::init();
 
     let input = advent_of_code::load_input(PROBLEM_NAME);
+
+    // If the input has more than one line, each line is a separate agent's
+    // route instead of the puzzle's single shared route.
+    let streams: Vec<_> = input.lines().map(decode_directions).collect();
+    if streams.len() > 1 {
+        let simulation = Simulation::with_streams(&streams);
+        log::info!("Houses visited = {}", simulation.houses().count());
+        log::info!("Shared houses = {}", simulation.shared_houses().len());
+        log::debug!("Route map:\n{}", simulation.render());
+        return;
+    }
+
     let directions = decode_directions(&input);
 
     let unique_locations = find_unique_locations(&directions, 1);
@@ -66,6 +276,17 @@ fn main() {
 
     let inverted_directions = find_unique_locations(&directions, 2);
     log::info!("Part 2 = {}", inverted_directions.len());
+
+    // Any other schedule can be provided as the first argument.
+    if let Some(schedule) = advent_of_code::utils::arg::<Schedule>(0) {
+        let simulation = Simulation::with_schedule(&directions, &schedule);
+        let (min, max) = simulation.bounding_box();
+
+        log::info!("Houses visited = {}", simulation.houses().count());
+        log::info!("Shared houses = {}", simulation.shared_houses().len());
+        log::info!("Houses are between {} and {}", min, max);
+        log::debug!("Route map:\n{}", simulation.render());
+    }
 }
 
 /// Decode directions from input string.
@@ -76,21 +297,8 @@ fn decode_directions(input: &str) -> Vec<Direction> {
 /// Count the number of unique locations visited by having multiple movers,
 /// going to the next mover each direction.
 fn find_unique_locations(directions: &[Direction], movers: usize) -> HashSet<Point> {
-    let mut locations = HashSet::new();
-    // We need to include our starting point.
-    locations.insert(Point(0, 0));
-
-    // Maintain a point for each mover.
-    let mut points = vec![Point(0, 0); movers];
-
-    // Iterate through each direction, looking at which step we're on. Find the
-    // correct point to update based on which mover is currently active.
-    for (index, direction) in directions.iter().enumerate() {
-        let point = &mut points[index % movers];
-        *point += direction.movement();
-        log::trace!("Evaluating point {}, moved to {}", index % movers, point);
-        locations.insert(*point);
-    }
+    let simulation = Simulation::with_schedule(directions, &Schedule::RoundRobin(movers));
+    let locations: HashSet<_> = simulation.houses().map(|(point, _house)| point).collect();
 
     log::debug!("Directions had {} unique points", locations.len());
 
@@ -157,4 +365,90 @@ mod tests {
             assert_eq!(unique_locations.len(), *count);
         }
     }
+
+    #[test]
+    fn test_schedule_from_str() {
+        advent_of_code::init();
+
+        assert_eq!("2".parse(), Ok(Schedule::RoundRobin(2)));
+        assert_eq!("0,0,1".parse(), Ok(Schedule::Pattern(vec![0, 0, 1])));
+        assert!("0".parse::<Schedule>().is_err());
+        assert!("a".parse::<Schedule>().is_err());
+        assert!("0,a".parse::<Schedule>().is_err());
+
+        let schedule = Schedule::Pattern(vec![0, 0, 2]);
+        assert_eq!(schedule.agents(), 3);
+        let agents: Vec<_> = (0..5).map(|index| schedule.agent(index)).collect();
+        assert_eq!(agents, vec![0, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_simulation_visits() {
+        advent_of_code::init();
+
+        let directions = decode_directions("^v^v");
+        let simulation = Simulation::with_schedule(&directions, &Schedule::RoundRobin(1));
+
+        let origin = simulation.house(Point(0, 0)).unwrap();
+        assert_eq!(origin.visits, 3);
+        assert_eq!(origin.agents, vec![0].into_iter().collect());
+        assert_eq!(simulation.house(Point(0, -1)).unwrap().visits, 2);
+        assert_eq!(simulation.house(Point(1, 0)), None);
+        assert!(simulation.shared_houses().is_empty());
+    }
+
+    #[test]
+    fn test_simulation_shared_houses() {
+        advent_of_code::init();
+
+        // Both agents go north, then one goes east and the other west.
+        let directions = decode_directions("^^><");
+        let simulation = Simulation::with_schedule(&directions, &Schedule::RoundRobin(2));
+        assert_eq!(simulation.shared_houses(), vec![Point(0, -1), Point(0, 0)]);
+        assert_eq!(simulation.house(Point(0, 0)).unwrap().visits, 2);
+
+        // The same routes given as separate streams.
+        let streams = vec![decode_directions("^>"), decode_directions("^<")];
+        let streamed = Simulation::with_streams(&streams);
+        assert_eq!(streamed.shared_houses(), simulation.shared_houses());
+        assert_eq!(streamed.houses().count(), simulation.houses().count());
+    }
+
+    #[test]
+    fn test_simulation_pattern() {
+        advent_of_code::init();
+
+        // The first agent takes two steps for each step of the second.
+        let directions = decode_directions(">>^>>^");
+        let schedule: Schedule = "0,0,1".parse().unwrap();
+        let simulation = Simulation::with_schedule(&directions, &schedule);
+
+        assert!(simulation.house(Point(4, 0)).is_some());
+        assert!(simulation.house(Point(0, -2)).is_some());
+        assert_eq!(simulation.houses().count(), 7);
+
+        // Agents missing from the pattern never start, so they don't share the
+        // origin with anyone.
+        let directions = decode_directions("^v");
+        let schedule: Schedule = "0,5".parse().unwrap();
+        assert_eq!(schedule.members(), vec![0, 5].into_iter().collect());
+        let simulation = Simulation::with_schedule(&directions, &schedule);
+        let origin = simulation.house(Point(0, 0)).unwrap();
+        assert_eq!(origin.visits, 2);
+        assert_eq!(origin.agents, vec![0, 5].into_iter().collect());
+    }
+
+    #[test]
+    fn test_simulation_bounding_box() {
+        advent_of_code::init();
+
+        let directions = decode_directions("^>>vv<<<^>");
+        let simulation = Simulation::with_schedule(&directions, &Schedule::RoundRobin(1));
+        assert_eq!(simulation.bounding_box(), (Point(-1, -1), Point(2, 1)));
+        assert_eq!(simulation.render(), ".111\n12.1\n1111\n");
+
+        let simulation = Simulation::new(1);
+        assert_eq!(simulation.bounding_box(), (Point(0, 0), Point(0, 0)));
+        assert_eq!(simulation.render(), "1\n");
+    }
 }
@@ -3,7 +3,7 @@ const PROBLEM_NAME: &str = "2015-04";
 fn main() {
     advent_of_code::init();
 
-    let input = advent_of_code::load_input(&PROBLEM_NAME);
+    let input = advent_of_code::load_input(PROBLEM_NAME);
     let minimum_number = find_minimum_number(&input, 5);
     log::info!("Part 1 = {}", minimum_number);
 
@@ -30,7 +30,7 @@ fn find_minimum_number(input: &str, leading_zeros: usize) -> usize {
         log::trace!("Hash with {} was {}", num, h);
 
         // If they're equal, we've found our result.
-        if &h[..leading_zeros] == desired {
+        if h[..leading_zeros] == desired {
             log::debug!("Hash with {} had {} zeros: {}", num, leading_zeros, h);
             break num;
         }
@@ -48,6 +48,6 @@ mod tests {
         advent_of_code::init();
 
         let input = "aaaaa";
-        assert_eq!(find_minimum_number(&input, 1), 34);
+        assert_eq!(find_minimum_number(input, 1), 34);
     }
 }
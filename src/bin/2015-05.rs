@@ -1,49 +1,712 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use itertools::Itertools;
+use regex::Regex;
 
 const PROBLEM_NAME: &str = "2015-05";
 
+#[cfg(test)]
 static VOWELS: &[char] = &['a', 'e', 'i', 'o', 'u'];
+#[cfg(test)]
 static BLOCKLISTED: &[&str] = &["ab", "cd", "pq", "xy"];
 
+/// The rules for each part of the puzzle, as understood by [`RuleSet`].
+static DEFAULT_RULES: &str = "# At least 3 vowels, no blocklisted pairs, and a double letter.
+Part 1 = atleast 3 aeiou and forbid ab cd pq xy and repeat 1
+
+# A pair appearing twice and a letter repeated with one letter between.
+Part 2 = pair and repeat 2";
+
 fn main() {
     advent_of_code::init();
 
+    // A path to a file with different rules can be provided as the first
+    // argument, or `default` to use the normal rules.
+    let rules: RuleSet = match advent_of_code::utils::arg::<String>(0) {
+        Some(path) if path != "default" => std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|rules| rules.parse())
+            .unwrap_or_else(|err| panic!("invalid rules: {}", err)),
+        _ => DEFAULT_RULES.parse().unwrap(),
+    };
+
+    // An explanation of why each line is nice or naughty can be printed by
+    // passing `explain` as the second argument.
+    let explain = match advent_of_code::utils::arg::<String>(1).as_deref() {
+        None => false,
+        Some("explain") => true,
+        Some(mode) => panic!("unknown mode {}", mode),
+    };
+
     let input = advent_of_code::load_input(PROBLEM_NAME);
     let lines: Vec<String> = advent_of_code::utils::decode_line(&input);
 
-    let nice = lines.iter().filter(|line| is_nice_1(line)).count();
-    log::info!("Part 1 = {}", nice);
+    for (name, rule) in rules.rules() {
+        log::debug!("Evaluating {}: {}", name, rule);
+
+        let nice = lines.iter().filter(|line| rule.matches(line)).count();
+        log::info!("{} = {}", name, nice);
+    }
+
+    if explain {
+        print!("{}", build_report(&rules, &lines));
+    }
+}
+
+/// A rule a string must follow to be nice.
+#[derive(Clone, Debug)]
+enum Rule {
+    /// Contains at least this many characters from a set.
+    AtLeast(usize, Vec<char>),
+    /// Contains any of these substrings. Usually combined with `Not`.
+    Contains(Vec<String>),
+    /// Contains a character that repeats this many characters later.
+    Repeats(usize),
+    /// Contains the same pair of characters twice without overlapping.
+    RepeatedPair,
+    /// Matches a regular expression.
+    Regex(Regex),
+    /// Every rule must match.
+    And(Vec<Rule>),
+    /// Any rule must match.
+    Or(Vec<Rule>),
+    /// The rule must not match.
+    Not(Box<Rule>),
+}
+
+impl Rule {
+    /// Determine if a string follows this rule.
+    fn matches(&self, input: &str) -> bool {
+        match self {
+            Rule::AtLeast(n, chars) => contains_at_least(input, *n, chars),
+            Rule::Contains(substrings) => substrings
+                .iter()
+                .any(|substring| input.contains(substring.as_str())),
+            Rule::Repeats(above) => contains_repeating(input, *above),
+            Rule::RepeatedPair => pairs(input),
+            Rule::Regex(regex) => regex.is_match(input),
+            Rule::And(rules) => rules.iter().all(|rule| rule.matches(input)),
+            Rule::Or(rules) => rules.iter().any(|rule| rule.matches(input)),
+            Rule::Not(rule) => !rule.matches(input),
+        }
+    }
+
+    /// Determine if a string follows this rule, along with the evidence for
+    /// why it did or didn't.
+    fn explain(&self, input: &str) -> Explanation {
+        let mut causes = Vec::new();
+
+        let (matched, evidence) = match self {
+            Rule::AtLeast(n, chars) => {
+                let found = input.chars().filter(|c| chars.contains(c)).count();
+                (found >= *n, Evidence::Count { found, needed: *n })
+            }
+            Rule::Contains(substrings) => {
+                let first = substrings
+                    .iter()
+                    .filter_map(|substring| Some((input.find(substring.as_str())?, substring)))
+                    .min();
+
+                match first {
+                    Some((index, substring)) => (
+                        true,
+                        Evidence::Substring {
+                            substring: substring.clone(),
+                            offset: input[..index].chars().count(),
+                        },
+                    ),
+                    None => (false, Evidence::NotFound),
+                }
+            }
+            Rule::Repeats(above) => match find_repeating(input, *above) {
+                Some((character, first)) => (
+                    true,
+                    Evidence::Repeat {
+                        character,
+                        first,
+                        second: first + above,
+                    },
+                ),
+                None => (false, Evidence::NotFound),
+            },
+            Rule::RepeatedPair => match find_pair(input) {
+                Some((first, second)) => (
+                    true,
+                    Evidence::Pair {
+                        pair: input.chars().skip(first).take(2).collect(),
+                        first,
+                        second,
+                    },
+                ),
+                None => (false, Evidence::NotFound),
+            },
+            Rule::Regex(regex) => match regex.find(input) {
+                Some(found) => (
+                    true,
+                    Evidence::Regex {
+                        matched: found.as_str().to_string(),
+                        offset: input[..found.start()].chars().count(),
+                    },
+                ),
+                None => (false, Evidence::NotFound),
+            },
+            Rule::And(rules) => {
+                causes = rules.iter().map(|rule| rule.explain(input)).collect();
+                let matched = causes.iter().all(|cause| cause.matched);
+                (matched, Evidence::Rules)
+            }
+            Rule::Or(rules) => {
+                causes = rules.iter().map(|rule| rule.explain(input)).collect();
+                let matched = causes.iter().any(|cause| cause.matched);
+                (matched, Evidence::Rules)
+            }
+            Rule::Not(rule) => {
+                let inner = rule.explain(input);
+
+                // The evidence for the inner rule is also the evidence for
+                // this one, unless it came from other rules.
+                let evidence = match inner.evidence {
+                    Evidence::Rules => Evidence::Negated,
+                    ref evidence => evidence.clone(),
+                };
+
+                let matched = !inner.matched;
+                causes.push(inner);
+                (matched, evidence)
+            }
+        };
+
+        Explanation {
+            rule: self.to_string(),
+            matched,
+            evidence,
+            causes,
+        }
+    }
+}
+
+/// What was found in a string when checking a rule. Offsets are 0-indexed
+/// character positions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Evidence {
+    /// How many characters from a set were found, and how many were needed.
+    Count { found: usize, needed: usize },
+    /// The first substring that was found.
+    Substring { substring: String, offset: usize },
+    /// The first character found again after a distance.
+    Repeat {
+        character: char,
+        first: usize,
+        second: usize,
+    },
+    /// The first pair of characters found twice without overlapping.
+    Pair {
+        pair: String,
+        first: usize,
+        second: usize,
+    },
+    /// The first match of a regular expression.
+    Regex { matched: String, offset: usize },
+    /// Nothing the rule was looking for was found.
+    NotFound,
+    /// The result came from combining other rules.
+    Rules,
+    /// The result came from negating a combination of other rules.
+    Negated,
+}
+
+impl std::fmt::Display for Evidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Evidence::Count { found, needed } => write!(f, "found {}, needed {}", found, needed),
+            Evidence::Substring { substring, offset } => {
+                write!(f, "found {:?} at offset {}", substring, offset)
+            }
+            Evidence::Repeat {
+                character,
+                first,
+                second,
+            } => write!(f, "{:?} at offsets {} and {}", character, first, second),
+            Evidence::Pair {
+                pair,
+                first,
+                second,
+            } => write!(f, "{:?} at offsets {} and {}", pair, first, second),
+            Evidence::Regex { matched, offset } => {
+                write!(f, "matched {:?} at offset {}", matched, offset)
+            }
+            Evidence::NotFound => write!(f, "nothing found"),
+            Evidence::Rules => write!(f, "combined rules"),
+            Evidence::Negated => write!(f, "negated combined rules"),
+        }
+    }
+}
+
+/// Why a string did or didn't follow a rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Explanation {
+    /// The rule, formatted the same way it is parsed.
+    rule: String,
+    matched: bool,
+    evidence: Evidence,
+    /// The explanations for each rule this rule was made from.
+    causes: Vec<Explanation>,
+}
+
+impl Explanation {
+    /// Every explanation that isn't only a combination of other rules.
+    fn checks(&self) -> Vec<&Explanation> {
+        match self.evidence {
+            Evidence::Rules => self.causes.iter().flat_map(Self::checks).collect(),
+            _ => vec![self],
+        }
+    }
+
+    /// The checks responsible for the rule not matching, or nothing if it
+    /// matched.
+    fn failures(&self) -> Vec<&Explanation> {
+        if self.matched {
+            return Vec::new();
+        }
+
+        match self.evidence {
+            Evidence::Rules => self.causes.iter().flat_map(Self::failures).collect(),
+            _ => vec![self],
+        }
+    }
+}
+
+/// Explanations for every line under every rule, and how often each check
+/// failed.
+#[derive(Debug)]
+struct Report<'a> {
+    lines: Vec<LineReport<'a>>,
+    /// The number of lines each check failed on, keyed by the rule name and
+    /// check.
+    summary: BTreeMap<String, usize>,
+}
+
+/// Explanations for a single line under every rule.
+#[derive(Debug)]
+struct LineReport<'a> {
+    line: &'a str,
+    explanations: Vec<(&'a str, Explanation)>,
+}
+
+/// Explain every line under every rule.
+fn build_report<'a>(rules: &'a RuleSet, lines: &'a [String]) -> Report<'a> {
+    let mut summary = BTreeMap::new();
+
+    let lines = lines
+        .iter()
+        .map(|line| {
+            let explanations: Vec<_> = rules
+                .rules()
+                .map(|(name, rule)| (name, rule.explain(line)))
+                .collect();
+
+            for (name, explanation) in &explanations {
+                for failure in explanation.failures() {
+                    *summary
+                        .entry(format!("{}: {}", name, failure.rule))
+                        .or_default() += 1;
+                }
+            }
+
+            LineReport { line, explanations }
+        })
+        .collect();
+
+    Report { lines, summary }
+}
+
+impl std::fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line.line)?;
+
+            for (name, explanation) in &line.explanations {
+                let status = if explanation.matched {
+                    "nice"
+                } else {
+                    "naughty"
+                };
+                writeln!(f, "  {}: {}", name, status)?;
+
+                for check in explanation.checks() {
+                    let status = if check.matched { "pass" } else { "fail" };
+                    writeln!(f, "    {} {}: {}", status, check.rule, check.evidence)?;
+                }
+            }
+        }
+
+        if !self.summary.is_empty() {
+            writeln!(f, "Failures:")?;
+        }
+
+        for (check, count) in &self.summary {
+            writeln!(f, "  {}: {}", check, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+
+    /// Parse a rule from an expression such as
+    /// `atleast 3 aeiou and not forbid ab cd`.
+    ///
+    /// Rules are `atleast N CHARS`, `contains WORDS...`, `forbid WORDS...`
+    /// (which is the same as `not contains`), `repeat DISTANCE`, `pair`, and
+    /// `regex PATTERN`, where patterns may not contain spaces. They can be
+    /// combined with `not`, `and`, and `or`, in that order of precedence, and
+    /// grouped with standalone parentheses.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<_> = s.split_whitespace().collect();
+        let mut parser = RuleParser { tokens, pos: 0 };
+
+        let rule = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(rule),
+            Some(token) => Err(format!("unexpected {}", token)),
+        }
+    }
+}
+
+/// A recursive descent parser for rule expressions.
+struct RuleParser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> RuleParser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek()?;
+        self.pos += 1;
+
+        Some(token)
+    }
+
+    /// Take the next token, which must exist.
+    fn expect(&mut self, what: &str) -> Result<&'a str, String> {
+        self.next()
+            .ok_or_else(|| format!("expected {} but rule ended", what))
+    }
+
+    /// Parse rules separated by `or`.
+    fn parse_or(&mut self) -> Result<Rule, String> {
+        let mut rules = vec![self.parse_and()?];
+
+        while self.peek() == Some("or") {
+            self.pos += 1;
+            rules.push(self.parse_and()?);
+        }
+
+        Ok(if rules.len() == 1 {
+            rules.remove(0)
+        } else {
+            Rule::Or(rules)
+        })
+    }
+
+    /// Parse rules separated by `and`.
+    fn parse_and(&mut self) -> Result<Rule, String> {
+        let mut rules = vec![self.parse_not()?];
+
+        while self.peek() == Some("and") {
+            self.pos += 1;
+            rules.push(self.parse_not()?);
+        }
+
+        Ok(if rules.len() == 1 {
+            rules.remove(0)
+        } else {
+            Rule::And(rules)
+        })
+    }
+
+    /// Parse a rule optionally preceded by `not`.
+    fn parse_not(&mut self) -> Result<Rule, String> {
+        if self.peek() == Some("not") {
+            self.pos += 1;
+            return Ok(Rule::Not(Box::new(self.parse_not()?)));
+        }
+
+        self.parse_atom()
+    }
+
+    /// Parse a single rule or a group in parentheses.
+    fn parse_atom(&mut self) -> Result<Rule, String> {
+        match self.expect("a rule")? {
+            "(" => {
+                let rule = self.parse_or()?;
+
+                match self.next() {
+                    Some(")") => Ok(rule),
+                    _ => Err("expected )".to_string()),
+                }
+            }
+            "atleast" => {
+                let count = self.expect("a count")?;
+                let count = count
+                    .parse()
+                    .map_err(|_| format!("{} is not a count", count))?;
+                let chars = self.expect("characters")?.chars().collect();
+
+                Ok(Rule::AtLeast(count, chars))
+            }
+            "contains" => Ok(Rule::Contains(self.parse_words()?)),
+            "forbid" => Ok(Rule::Not(Box::new(Rule::Contains(self.parse_words()?)))),
+            "repeat" => {
+                let distance = self.expect("a distance")?;
+                match distance.parse() {
+                    Ok(0) | Err(_) => Err(format!("{} is not a distance", distance)),
+                    Ok(distance) => Ok(Rule::Repeats(distance)),
+                }
+            }
+            "pair" => Ok(Rule::RepeatedPair),
+            "regex" => {
+                let pattern = self.expect("a pattern")?;
+                let regex = Regex::new(pattern).map_err(|err| err.to_string())?;
+
+                Ok(Rule::Regex(regex))
+            }
+            token => Err(format!("unknown rule {}", token)),
+        }
+    }
+
+    /// Parse words until the end of the rule or the next operator.
+    fn parse_words(&mut self) -> Result<Vec<String>, String> {
+        let mut words = Vec::new();
+
+        while let Some(word) = self.peek() {
+            if matches!(word, "and" | "or" | "not" | "(" | ")") {
+                break;
+            }
+
+            words.push(word.to_string());
+            self.pos += 1;
+        }
+
+        if words.is_empty() {
+            return Err("expected at least one word".to_string());
+        }
+
+        Ok(words)
+    }
+}
+
+impl std::fmt::Display for Rule {
+    /// Format the rule in the same format it is parsed from.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// Join rules with an operator, grouping any rules that would
+        /// otherwise bind differently.
+        fn join(
+            f: &mut std::fmt::Formatter<'_>,
+            rules: &[Rule],
+            operator: &str,
+        ) -> std::fmt::Result {
+            for (index, rule) in rules.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " {} ", operator)?;
+                }
+
+                match rule {
+                    Rule::And(_) | Rule::Or(_) => write!(f, "( {} )", rule)?,
+                    _ => write!(f, "{}", rule)?,
+                }
+            }
+
+            Ok(())
+        }
 
-    let nice = lines.iter().filter(|line| is_nice_2(line)).count();
-    log::info!("Part 2 = {}", nice);
+        match self {
+            Rule::AtLeast(n, chars) => {
+                write!(f, "atleast {} {}", n, chars.iter().collect::<String>())
+            }
+            Rule::Contains(words) => write!(f, "contains {}", words.join(" ")),
+            Rule::Repeats(distance) => write!(f, "repeat {}", distance),
+            Rule::RepeatedPair => write!(f, "pair"),
+            Rule::Regex(regex) => write!(f, "regex {}", regex),
+            Rule::And(rules) => join(f, rules, "and"),
+            Rule::Or(rules) => join(f, rules, "or"),
+            Rule::Not(rule) => match rule.as_ref() {
+                Rule::Contains(words) => write!(f, "forbid {}", words.join(" ")),
+                Rule::And(_) | Rule::Or(_) => write!(f, "not ( {} )", rule),
+                _ => write!(f, "not {}", rule),
+            },
+        }
+    }
+}
+
+/// A collection of named rules.
+///
+/// Rule sets are parsed from text with one rule per line, formatted as the
+/// name, an `=`, and the rule. Blank lines and lines starting with `#` are
+/// ignored.
+///
+/// ```text
+/// Part 1 = atleast 3 aeiou and forbid ab cd pq xy and repeat 1
+/// Part 2 = pair and repeat 2
+/// ```
+#[derive(Clone, Debug)]
+struct RuleSet {
+    rules: Vec<(String, Rule)>,
+}
+
+impl RuleSet {
+    /// Every rule along with its name, in the order they were defined.
+    fn rules(&self) -> impl Iterator<Item = (&str, &Rule)> {
+        self.rules.iter().map(|(name, rule)| (name.as_str(), rule))
+    }
+
+    /// Find a rule by name.
+    #[cfg(test)]
+    fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules()
+            .find(|(rule_name, _rule)| *rule_name == name)
+            .map(|(_name, rule)| rule)
+    }
+}
+
+impl std::str::FromStr for RuleSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules: Vec<(String, Rule)> = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| format!("line {}: {}", index + 1, message);
+
+            let split = line
+                .find('=')
+                .ok_or_else(|| error("expected name = rule".to_string()))?;
+            let name = line[..split].trim();
+            if name.is_empty() {
+                return Err(error("rule is missing a name".to_string()));
+            }
+
+            if rules.iter().any(|(existing, _rule)| existing == name) {
+                return Err(error(format!("{} was already defined", name)));
+            }
+
+            let rule = line[split + 1..].parse().map_err(error)?;
+            rules.push((name.to_string(), rule));
+        }
+
+        Ok(Self { rules })
+    }
 }
 
 /// Determine if an input is nice by ensuring it has at least 3 vowels, contains
-/// no blocklisted words, and contains repeating characters.
+/// no blocklisted words, and contains repeating characters. Used to check
+/// [`DEFAULT_RULES`].
+#[cfg(test)]
 fn is_nice_1(line: &str) -> bool {
-    contains_n_vowels(&line, 3) && !blocklisted(&line) && contains_repeating(&line, 1)
+    contains_n_vowels(line, 3) && !blocklisted(line) && contains_repeating(line, 1)
 }
 
 /// Determine if an input is nice by ensuring it has two non-overlapping pairs
-/// and contains a letter that is repeated after a different letter.
+/// and contains a letter that is repeated after a different letter. Used to
+/// check [`DEFAULT_RULES`].
+#[cfg(test)]
 fn is_nice_2(line: &str) -> bool {
-    pairs(&line) && contains_repeating(&line, 2)
+    pairs(line) && contains_repeating(line, 2)
+}
+
+/// Determine if an input contains at least n characters from a set.
+fn contains_at_least(input: &str, n: usize, chars: &[char]) -> bool {
+    input.chars().filter(|c| chars.contains(c)).count() >= n
 }
 
 /// Determine if an input contains at least n number of vowels.
+#[cfg(test)]
 fn contains_n_vowels(input: &str, n: usize) -> bool {
-    input.chars().filter(|c| VOWELS.contains(c)).count() >= n
+    contains_at_least(input, n, VOWELS)
 }
 
 /// Determine if the input contains blocklisted characters.
+#[cfg(test)]
 fn blocklisted(input: &str) -> bool {
     BLOCKLISTED.iter().any(|letters| input.contains(letters))
 }
 
 /// Determine if the input has characters that repeat n characters above it.
 fn contains_repeating(input: &str, above: usize) -> bool {
+    find_repeating(input, above).is_some()
+}
+
+/// Find the first character that repeats n characters above it, and its
+/// character offset.
+fn find_repeating(input: &str, above: usize) -> Option<(char, usize)> {
+    // ASCII input can be compared a byte at a time, anything else needs to be
+    // split into characters so distances are still measured in characters.
+    if input.is_ascii() {
+        let bytes = input.as_bytes();
+        first_repeat(bytes, above).map(|index| (bytes[index] as char, index))
+    } else {
+        let chars: Vec<_> = input.chars().collect();
+        first_repeat(&chars, above).map(|index| (chars[index], index))
+    }
+}
+
+/// Find the first item that is equal to the item a distance after it by
+/// comparing the items against themselves offset by the distance.
+fn first_repeat<T: PartialEq>(items: &[T], distance: usize) -> Option<usize> {
+    let later = items.get(distance..)?;
+
+    items.iter().zip(later).position(|(a, b)| a == b)
+}
+
+/// Determine if there is a non-overlapping pair of the same character.
+fn pairs(input: &str) -> bool {
+    find_pair(input).is_some()
+}
+
+/// Find the character offsets of the first pair of characters to appear twice
+/// without overlapping.
+fn find_pair(input: &str) -> Option<(usize, usize)> {
+    if input.is_ascii() {
+        repeated_pair(input.as_bytes())
+    } else {
+        repeated_pair(&input.chars().collect::<Vec<_>>())
+    }
+}
+
+/// Find the positions of the first two adjacent items to appear again later
+/// without overlapping.
+///
+/// Only the first position of each pair needs to be remembered, as it is the
+/// position most likely to be far enough away from any later occurrence.
+fn repeated_pair<T: Copy + Eq + std::hash::Hash>(items: &[T]) -> Option<(usize, usize)> {
+    let mut first_seen: HashMap<(T, T), usize> = HashMap::new();
+
+    for (index, window) in items.windows(2).enumerate() {
+        let first = *first_seen.entry((window[0], window[1])).or_insert(index);
+
+        if index >= first + 2 {
+            log::trace!("Found pair at {} and {}", first, index);
+            return Some((first, index));
+        }
+    }
+
+    None
+}
+
+/// Determine if the input has characters that repeat n characters above it.
+/// Used to check [`contains_repeating`].
+#[cfg(test)]
+fn contains_repeating_naive(input: &str, above: usize) -> bool {
     input.chars().enumerate().any(|(index, c)| {
         if let Some(next) = input.chars().nth(index + above) {
             c == next
@@ -53,8 +716,16 @@ fn contains_repeating(input: &str, above: usize) -> bool {
     })
 }
 
-/// Determine if there is a non-overlapping pair of the same character.
-fn pairs(input: &str) -> bool {
+/// Determine if there is a non-overlapping pair of the same character. Used to
+/// check [`pairs`].
+///
+/// # Panics
+///
+/// Will panic on empty or non-ASCII input.
+#[cfg(test)]
+fn pairs_naive(input: &str) -> bool {
+    use itertools::Itertools;
+
     // Store the positions of each possible character combination.
     let mut pairs: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
 
@@ -99,19 +770,20 @@ fn pairs(input: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_contains_n_vowels() {
         advent_of_code::init();
 
         let input = "aeiou";
-        assert_eq!(contains_n_vowels(&input, 3), true);
+        assert!(contains_n_vowels(input, 3));
 
         let input = "bcdfg";
-        assert_eq!(contains_n_vowels(&input, 3), false);
+        assert!(!contains_n_vowels(input, 3));
 
         let input = "ugknbfddgicrmopn";
-        assert_eq!(contains_n_vowels(&input, 3), true);
+        assert!(contains_n_vowels(input, 3));
     }
 
     #[test]
@@ -119,13 +791,13 @@ mod tests {
         advent_of_code::init();
 
         let input = "abcdefg";
-        assert_eq!(blocklisted(&input), true);
+        assert!(blocklisted(input));
 
         let input = "asdf";
-        assert_eq!(blocklisted(&input), false);
+        assert!(!blocklisted(input));
 
         let input = "ugknbfddgicrmopn";
-        assert_eq!(blocklisted(&input), false);
+        assert!(!blocklisted(input));
     }
 
     #[test]
@@ -133,16 +805,16 @@ mod tests {
         advent_of_code::init();
 
         let input = "abcdefg";
-        assert_eq!(contains_repeating(&input, 1), false);
+        assert!(!contains_repeating(input, 1));
 
         let input = "abbb";
-        assert_eq!(contains_repeating(&input, 1), true);
+        assert!(contains_repeating(input, 1));
 
         let input = "ugknbfddgicrmopn";
-        assert_eq!(contains_repeating(&input, 1), true);
+        assert!(contains_repeating(input, 1));
 
         let input = "abcdefeghi";
-        assert_eq!(contains_repeating(&input, 2), true);
+        assert!(contains_repeating(input, 2));
     }
 
     #[test]
@@ -150,10 +822,47 @@ mod tests {
         advent_of_code::init();
 
         let input = pairs("aabcdefgaa");
-        assert_eq!(input, true);
+        assert!(input);
 
         let input = pairs("aaa");
-        assert_eq!(input, false);
+        assert!(!input);
+
+        assert!(pairs("aaaa"));
+        assert!(pairs("xyxy"));
+        assert!(!pairs("xyx"));
+        assert!(!pairs(""));
+        assert!(!pairs("a"));
+    }
+
+    #[test]
+    fn test_non_ascii() {
+        advent_of_code::init();
+
+        assert!(pairs("éaéa"));
+        assert!(pairs("ééxéé"));
+        assert!(!pairs("ééé"));
+        assert!(!pairs("aéb"));
+
+        assert!(contains_repeating("éé", 1));
+        assert!(contains_repeating("éxé", 2));
+        assert!(!contains_repeating("éxé", 1));
+        assert!(!contains_repeating("aéa", 1));
+        assert!(contains_repeating("aéa", 2));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_contains_repeating_matches_naive(input in "\\PC{0,20}", above in 0usize..4) {
+            prop_assert_eq!(
+                contains_repeating(&input, above),
+                contains_repeating_naive(&input, above)
+            );
+        }
+
+        #[test]
+        fn prop_pairs_matches_naive(input in "[abc]{1,20}") {
+            prop_assert_eq!(pairs(&input), pairs_naive(&input));
+        }
     }
 
     #[test]
@@ -168,7 +877,7 @@ mod tests {
             ("dvszwmarrgswjxmb", false),
         ];
         for (input, nice) in inputs {
-            let is_nice = is_nice_1(&input);
+            let is_nice = is_nice_1(input);
             assert_eq!(is_nice, *nice, "input {} is {}", input, nice);
         }
     }
@@ -184,8 +893,304 @@ mod tests {
             ("ieodomkazucvgmuy", false),
         ];
         for (input, nice) in inputs {
-            let is_nice = is_nice_2(&input);
+            let is_nice = is_nice_2(input);
             assert_eq!(is_nice, *nice, "input {} is {}", input, nice);
         }
     }
+
+    #[test]
+    fn test_rule_from_str() {
+        advent_of_code::init();
+
+        let cases = &[
+            "atleast 3 aeiou",
+            "forbid ab cd",
+            "contains ab cd",
+            "repeat 2",
+            "pair",
+            "regex ^a.*z$",
+            "pair and repeat 2",
+            "not pair or repeat 1",
+            "not ( pair or repeat 1 )",
+            "( pair or repeat 1 ) and atleast 1 a",
+        ];
+        for case in cases {
+            let rule: Rule = case.parse().unwrap();
+            assert_eq!(rule.to_string(), *case);
+        }
+
+        let rule: Rule = "not not contains ab".parse().unwrap();
+        assert_eq!(rule.to_string(), "not forbid ab");
+
+        let errors = &[
+            ("", "expected a rule but rule ended"),
+            ("atleast a aeiou", "a is not a count"),
+            ("atleast 3", "expected characters but rule ended"),
+            ("forbid", "expected at least one word"),
+            ("repeat 0", "0 is not a distance"),
+            ("pair and", "expected a rule but rule ended"),
+            ("pair pair", "unexpected pair"),
+            ("( pair", "expected )"),
+            ("unknown", "unknown rule unknown"),
+        ];
+        for (input, error) in errors {
+            assert_eq!(input.parse::<Rule>().unwrap_err(), *error, "{}", input);
+        }
+        assert!("regex (".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_rule_matches() {
+        advent_of_code::init();
+
+        let rule: Rule = "pair or regex ^z".parse().unwrap();
+        assert!(rule.matches("abab"));
+        assert!(rule.matches("zoo"));
+        assert!(!rule.matches("abc"));
+
+        let rule: Rule = "atleast 2 xyz and not ( contains q or repeat 1 )"
+            .parse()
+            .unwrap();
+        assert!(rule.matches("xay"));
+        assert!(!rule.matches("xyq"));
+        assert!(!rule.matches("xxy"));
+        assert!(!rule.matches("xab"));
+    }
+
+    #[test]
+    fn test_rule_set_from_str() {
+        advent_of_code::init();
+
+        let rules: RuleSet = DEFAULT_RULES.parse().unwrap();
+        let names: Vec<_> = rules.rules().map(|(name, _rule)| name).collect();
+        assert_eq!(names, vec!["Part 1", "Part 2"]);
+
+        assert_eq!(
+            "pair".parse::<RuleSet>().unwrap_err(),
+            "line 1: expected name = rule"
+        );
+        assert_eq!(
+            " = pair".parse::<RuleSet>().unwrap_err(),
+            "line 1: rule is missing a name"
+        );
+        assert_eq!(
+            "a = pair\n\na = pair".parse::<RuleSet>().unwrap_err(),
+            "line 3: a was already defined"
+        );
+        assert_eq!(
+            "a = pear".parse::<RuleSet>().unwrap_err(),
+            "line 1: unknown rule pear"
+        );
+    }
+
+    #[test]
+    fn test_default_rules() {
+        advent_of_code::init();
+
+        let rules: RuleSet = DEFAULT_RULES.parse().unwrap();
+        let part_1 = rules.rule("Part 1").unwrap();
+        let part_2 = rules.rule("Part 2").unwrap();
+
+        let inputs = &[
+            "ugknbfddgicrmopn",
+            "aaa",
+            "jchzalrnumimnmhp",
+            "haegwjzuvuyypxyu",
+            "dvszwmarrgswjxmb",
+            "qjhvhtzxzqqjkmpb",
+            "xxyxx",
+            "uurcxstgmygtbstg",
+            "ieodomkazucvgmuy",
+            "aaaa",
+            "abcdde",
+        ];
+        for input in inputs {
+            assert_eq!(part_1.matches(input), is_nice_1(input), "{}", input);
+            assert_eq!(part_2.matches(input), is_nice_2(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_explain_evidence() {
+        advent_of_code::init();
+
+        let explain = |rule: &str, input: &str| rule.parse::<Rule>().unwrap().explain(input);
+
+        let explanation = explain("atleast 3 aeiou", "haegwjzuvuyypxyu");
+        assert!(explanation.matched);
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Count {
+                found: 5,
+                needed: 3
+            }
+        );
+
+        let explanation = explain("forbid ab cd pq xy", "haegwjzuvuyypxyu");
+        assert!(!explanation.matched);
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Substring {
+                substring: "xy".to_string(),
+                offset: 13,
+            }
+        );
+        assert_eq!(
+            explanation.evidence.to_string(),
+            "found \"xy\" at offset 13"
+        );
+
+        // The earliest substring is reported, not the first one listed.
+        let explanation = explain("contains cd ab", "éabcd");
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Substring {
+                substring: "ab".to_string(),
+                offset: 1,
+            }
+        );
+
+        let explanation = explain("repeat 1", "ugknbfddgicrmopn");
+        assert_eq!(explanation.evidence.to_string(), "'d' at offsets 6 and 7");
+
+        let explanation = explain("pair", "qjhvhtzxzqqjkmpb");
+        assert_eq!(
+            explanation.evidence,
+            Evidence::Pair {
+                pair: "qj".to_string(),
+                first: 0,
+                second: 10,
+            }
+        );
+
+        let explanation = explain("pair", "ieodomkazucvgmuy");
+        assert!(!explanation.matched);
+        assert_eq!(explanation.evidence, Evidence::NotFound);
+
+        let explanation = explain("regex [0-9]+", "ab12c");
+        assert_eq!(
+            explanation.evidence.to_string(),
+            "matched \"12\" at offset 2"
+        );
+    }
+
+    #[test]
+    fn test_explain_failures() {
+        advent_of_code::init();
+
+        let rules: RuleSet = DEFAULT_RULES.parse().unwrap();
+
+        let explanation = rules.rule("Part 1").unwrap().explain("dvszwmarrgswjxmb");
+        assert!(!explanation.matched);
+        let failures: Vec<_> = explanation
+            .failures()
+            .iter()
+            .map(|failure| (failure.rule.as_str(), failure.evidence.to_string()))
+            .collect();
+        assert_eq!(
+            failures,
+            vec![("atleast 3 aeiou", "found 1, needed 3".to_string())]
+        );
+        assert_eq!(explanation.checks().len(), 3);
+
+        let explanation = rules.rule("Part 2").unwrap().explain("qjhvhtzxzqqjkmpb");
+        assert!(explanation.matched);
+        assert!(explanation.failures().is_empty());
+
+        // Every option of a failed or is responsible.
+        let rule: Rule = "not ( pair or repeat 1 ) or repeat 2".parse().unwrap();
+        let explanation = rule.explain("aab");
+        let failures: Vec<_> = explanation
+            .failures()
+            .iter()
+            .map(|failure| failure.rule.as_str())
+            .collect();
+        assert_eq!(failures, vec!["not ( pair or repeat 1 )", "repeat 2"]);
+        assert_eq!(explanation.causes[0].evidence, Evidence::Negated);
+    }
+
+    #[test]
+    fn test_explain_matches() {
+        advent_of_code::init();
+
+        let rules: RuleSet = DEFAULT_RULES.parse().unwrap();
+        let other: Rule = "not ( pair or regex ^x ) or atleast 2 é".parse().unwrap();
+
+        for input in &[
+            "ugknbfddgicrmopn",
+            "aaa",
+            "xxyxx",
+            "éé",
+            "xé",
+            "ieodomkazucvgmuy",
+        ] {
+            for (_name, rule) in rules.rules() {
+                assert_eq!(rule.explain(input).matched, rule.matches(input));
+            }
+
+            assert_eq!(other.explain(input).matched, other.matches(input));
+        }
+    }
+
+    #[test]
+    fn test_build_report() {
+        advent_of_code::init();
+
+        let rules: RuleSet = DEFAULT_RULES.parse().unwrap();
+        let lines = vec![
+            "ugknbfddgicrmopn".to_string(),
+            "jchzalrnumimnmhp".to_string(),
+            "haegwjzuvuyypxyu".to_string(),
+        ];
+
+        let report = build_report(&rules, &lines);
+        assert_eq!(report.lines.len(), 3);
+        assert_eq!(report.summary["Part 1: repeat 1"], 1);
+        assert_eq!(report.summary["Part 1: forbid ab cd pq xy"], 1);
+        assert_eq!(report.summary["Part 2: pair"], 3);
+        assert_eq!(report.summary["Part 2: repeat 2"], 1);
+        assert_eq!(report.summary.len(), 4);
+
+        let text = report.to_string();
+        assert!(text.starts_with(
+            "ugknbfddgicrmopn
+  Part 1: nice
+    pass atleast 3 aeiou: found 3, needed 3
+    pass forbid ab cd pq xy: nothing found
+    pass repeat 1: 'd' at offsets 6 and 7
+  Part 2: naughty
+    fail pair: nothing found
+    fail repeat 2: nothing found
+"
+        ));
+        assert!(text.ends_with(
+            "Failures:
+  Part 1: forbid ab cd pq xy: 1
+  Part 1: repeat 1: 1
+  Part 2: pair: 3
+  Part 2: repeat 2: 1
+"
+        ));
+    }
+
+    #[test]
+    fn test_generated_input() {
+        advent_of_code::init();
+
+        let rules: RuleSet = DEFAULT_RULES.parse().unwrap();
+        let part_1 = rules.rule("Part 1").unwrap();
+        let part_2 = rules.rule("Part 2").unwrap();
+
+        for input in advent_of_code::generate::inputs(PROBLEM_NAME, 3, 500) {
+            for line in input.lines() {
+                assert_eq!(part_1.matches(line), is_nice_1(line), "{}", line);
+                assert_eq!(part_2.matches(line), is_nice_2(line), "{}", line);
+                assert_eq!(part_1.explain(line).matched, is_nice_1(line), "{}", line);
+            }
+
+            // The comparison is only useful if some strings are nice.
+            assert!(input.lines().any(is_nice_1));
+            assert!(input.lines().any(is_nice_2));
+        }
+    }
 }
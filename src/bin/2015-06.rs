@@ -41,7 +41,7 @@ impl std::str::FromStr for Command {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let captures = COMMAND.captures(&s).ok_or("line was not valid command")?;
+        let captures = COMMAND.captures(s).ok_or("line was not valid command")?;
 
         let action: Action = captures["action"].parse()?;
 
@@ -82,10 +82,8 @@ fn main() {
 /// Apply the initial actions for the set of commands where a light can only
 /// be in a binary position of on/off or 1/0.
 fn apply_command_1(lights: &mut [Vec<usize>], command: &Command) {
-    for x in command.from.0..=command.to.0 {
-        for y in command.from.1..=command.to.1 {
-            let cell = &mut lights[x][y];
-
+    for row in &mut lights[command.from.0..=command.to.0] {
+        for cell in &mut row[command.from.1..=command.to.1] {
             match command.action {
                 Action::TurnOn => *cell = 1,
                 Action::TurnOff => *cell = 0,
@@ -99,10 +97,8 @@ fn apply_command_1(lights: &mut [Vec<usize>], command: &Command) {
 /// brightness changes based on the command. It has a minimum brightness of 0
 /// and toggling it increases the brightness by 2.
 fn apply_command_2(lights: &mut [Vec<usize>], command: &Command) {
-    for x in command.from.0..=command.to.0 {
-        for y in command.from.1..=command.to.1 {
-            let cell = &mut lights[x][y];
-
+    for row in &mut lights[command.from.0..=command.to.0] {
+        for cell in &mut row[command.from.1..=command.to.1] {
             match command.action {
                 Action::TurnOn => *cell += 1,
                 // It's possible to subtract from an already off light, so we
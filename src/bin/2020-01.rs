@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use advent_of_code::answer::Answer;
+
 static PROBLEM_NAME: &str = "2020-01";
 
 fn main() {
@@ -9,15 +11,16 @@ fn main() {
     let nums: Vec<i32> = advent_of_code::utils::decode_line(&input);
 
     let pair = find_pair(2020, &nums);
-    log::info!("Part 1 = {}", pair.0 * pair.1);
+    log::info!("Part 1 = {}", Answer::product(vec![pair.0, pair.1]));
 
     let nums = find_three_pair(2020, &nums);
-    log::info!("Part 2 = {}", nums.0 * nums.1 * nums.2);
+    log::info!("Part 2 = {}", Answer::product(vec![nums.0, nums.1, nums.2]));
 }
 
 /// Attempt to find a pair that sum to some number.
 ///
-/// Runs in `O(n)` time.
+/// Runs in `O(n)` time. Remainders are calculated as `i64`, so they can't
+/// overflow for any `i32` inputs.
 ///
 /// # Panics
 ///
@@ -32,12 +35,12 @@ fn find_pair(sums_to: i32, nums: &[i32]) -> (i32, i32) {
     // calculate what other number is required. If this number has previously
     // been seen, we've found our pair.
     for num in nums {
-        if let Some(other) = index.get(num) {
+        if let Some(other) = index.get(&i64::from(*num)) {
             log::debug!("Found pair {} + {} = {}", num, other, sums_to);
             return (*num, *other);
         }
 
-        let remainder = sums_to - num;
+        let remainder = i64::from(sums_to) - i64::from(*num);
         log::trace!("Input {} has remainder {}", num, remainder);
 
         index.insert(remainder, *num);
@@ -49,6 +52,7 @@ fn find_pair(sums_to: i32, nums: &[i32]) -> (i32, i32) {
 /// Attempt to find three numbers that sum to some number.
 ///
 /// Runs in `O(n^3)` time. There are accessible `O(n^2)` to visit in the future.
+/// Sums are calculated as `i64`, so they can't overflow for any `i32` inputs.
 ///
 /// # Panics
 ///
@@ -74,12 +78,12 @@ fn find_three_pair(sums_to: i32, nums: &[i32]) -> (i32, i32, i32) {
                     continue;
                 }
 
-                let sum = n1 + n2 + n3;
+                let sum = i64::from(*n1) + i64::from(*n2) + i64::from(*n3);
                 log::trace!("Looking at combination {} + {} + {} = {}", n1, n2, n3, sum);
 
                 checks += 1;
 
-                if sum == sums_to {
+                if sum == i64::from(sums_to) {
                     log::debug!(
                         "Found three pair {} + {} + {} = {} with {} checks",
                         n1,
@@ -118,4 +122,34 @@ mod tests {
         let pair = find_three_pair(6, &[1, 2, 3, 4, 5]);
         assert_eq!(pair, (1, 2, 3));
     }
+
+    #[test]
+    fn test_large_product() {
+        advent_of_code::init();
+
+        let pair = find_pair(i32::MAX, &[i32::MAX - 1, 7, 1]);
+        assert_eq!(pair, (1, i32::MAX - 1));
+        assert_eq!(
+            Answer::product(vec![i32::MAX, i32::MAX, i32::MAX]).to_string(),
+            "9903520300447984150353281023"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unable to find pair")]
+    fn test_remainder_no_false_match() {
+        advent_of_code::init();
+
+        // Wrapping i32::MAX - -1 would give i32::MIN, falsely pairing it with -1.
+        find_pair(i32::MAX, &[-1, i32::MIN, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "unable to find numbers")]
+    fn test_sum_no_false_match() {
+        advent_of_code::init();
+
+        // Wrapping i32::MAX + i32::MAX + 0 would give -2.
+        find_three_pair(-2, &[i32::MAX, i32::MAX, 0]);
+    }
 }
@@ -25,7 +25,7 @@ impl std::str::FromStr for PasswordEntry {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let captures = PASSWORD_LINE.captures(&s).ok_or("unable to match regex")?;
+        let captures = PASSWORD_LINE.captures(s).ok_or("unable to match regex")?;
 
         Ok(PasswordEntry {
             // These should always be numbers.
@@ -42,69 +42,129 @@ impl std::str::FromStr for PasswordEntry {
     }
 }
 
-fn main() {
-    advent_of_code::init();
+/// A rule deciding if a password is valid for its entry.
+trait Policy {
+    /// The name used to select this policy from the command line.
+    fn name(&self) -> &'static str;
 
-    let input = advent_of_code::load_input(PROBLEM_NAME);
-    let entries: Vec<PasswordEntry> = advent_of_code::utils::decode_line(&input);
+    /// Determine if the password in the entry is valid.
+    fn is_valid(&self, entry: &PasswordEntry) -> bool;
+}
+
+/// The letter must occur between num1 and num2 times, inclusive.
+struct LetterCount;
 
-    let valid_passwords = count_valid_letter_counts(&entries);
-    log::info!("Part 1 = {}", valid_passwords);
+impl Policy for LetterCount {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn is_valid(&self, entry: &PasswordEntry) -> bool {
+        let count = entry.password.matches(entry.letter).count();
+
+        log::trace!(
+            "Password {} has letter {} appear {} times",
+            entry.password,
+            entry.letter,
+            count
+        );
 
-    let valid_passwords = count_valid_letter_positions(&entries);
-    log::info!("Part 2 = {}", valid_passwords);
+        entry.num1 <= count && count <= entry.num2
+    }
 }
 
-/// Validate that the letter in the entry occurs (num1, num2) times.
-fn validate_letter_count(entry: &PasswordEntry) -> bool {
-    let count = entry.password.matches(entry.letter).count();
+/// The letter must appear at exactly one of the 1-indexed positions num1 and
+/// num2. Positions outside of the password make the entry invalid.
+struct LetterPosition;
 
-    log::trace!(
-        "Password {} has letter {} appear {} times",
-        entry.password,
-        entry.letter,
-        count
-    );
+impl LetterPosition {
+    /// Check if the letter is at a 1-indexed position, or `None` if the
+    /// position is outside of the password.
+    fn letter_at(entry: &PasswordEntry, position: usize) -> Option<bool> {
+        let index = position.checked_sub(1)?;
+        let letter = entry.password.chars().nth(index)?;
 
-    entry.num1 <= count && count <= entry.num2
+        Some(letter == entry.letter)
+    }
 }
 
-/// Validate that the letter appears in num1 XOR num2.
-fn validate_letter_position(entry: &PasswordEntry) -> bool {
-    // We need to subtract one from these because rules are 1-indexed.
-    let pos1 = entry.password.chars().nth(entry.num1 - 1).unwrap() == entry.letter;
-    let pos2 = entry.password.chars().nth(entry.num2 - 1).unwrap() == entry.letter;
-
-    log::trace!(
-        "Password {} has letter {} in pos1: {}, pos2: {}",
-        entry.password,
-        entry.letter,
-        pos1,
-        pos2
-    );
-
-    pos1 ^ pos2
+impl Policy for LetterPosition {
+    fn name(&self) -> &'static str {
+        "position"
+    }
+
+    fn is_valid(&self, entry: &PasswordEntry) -> bool {
+        let (pos1, pos2) = match (
+            Self::letter_at(entry, entry.num1),
+            Self::letter_at(entry, entry.num2),
+        ) {
+            (Some(pos1), Some(pos2)) => (pos1, pos2),
+            _ => {
+                log::debug!(
+                    "Password {} does not have positions {} and {}",
+                    entry.password,
+                    entry.num1,
+                    entry.num2
+                );
+                return false;
+            }
+        };
+
+        log::trace!(
+            "Password {} has letter {} in pos1: {}, pos2: {}",
+            entry.password,
+            entry.letter,
+            pos1,
+            pos2
+        );
+
+        pos1 ^ pos2
+    }
 }
 
-/// Count the number of true values returned by [`validate_letter_count`].
-fn count_valid_letter_counts(entries: &[PasswordEntry]) -> usize {
-    entries
-        .iter()
-        .filter(|entry| validate_letter_count(entry))
-        .count()
+/// Every known policy, in the order of the parts they answer.
+static POLICIES: &[&(dyn Policy + Sync)] = &[&LetterCount, &LetterPosition];
+
+fn main() {
+    advent_of_code::init();
+
+    // The name of a single policy to apply can be provided as the first
+    // argument, otherwise all of them are applied.
+    let selected = advent_of_code::utils::arg::<String>(0).unwrap_or_else(|| "all".to_string());
+    if selected != "all" && !POLICIES.iter().any(|policy| policy.name() == selected) {
+        let names: Vec<_> = POLICIES.iter().map(|policy| policy.name()).collect();
+        log::error!(
+            "Unknown policy {}, expected all or one of: {}",
+            selected,
+            names.join(", ")
+        );
+        std::process::exit(1);
+    }
+
+    let input = advent_of_code::load_input(PROBLEM_NAME);
+    let entries: Vec<PasswordEntry> = advent_of_code::utils::decode_line(&input);
+
+    for (index, policy) in POLICIES.iter().enumerate() {
+        if selected != "all" && selected != policy.name() {
+            continue;
+        }
+
+        let valid_passwords = count_valid(&entries, *policy);
+        log::info!("Part {} = {}", index + 1, valid_passwords);
+    }
 }
 
-/// Count the number of true values returned by [`validate_letter_position`].
-fn count_valid_letter_positions(entries: &[PasswordEntry]) -> usize {
+/// Count the number of entries with a valid password under a policy.
+fn count_valid(entries: &[PasswordEntry], policy: &dyn Policy) -> usize {
     entries
         .iter()
-        .filter(|entry| validate_letter_position(entry))
+        .filter(|entry| policy.is_valid(entry))
         .count()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{validate_letter_count, validate_letter_position, PasswordEntry};
+    use super::*;
 
     #[test]
     fn test_password_entry_from_str() {
@@ -128,12 +188,12 @@ mod tests {
         advent_of_code::init();
 
         let entry: PasswordEntry = "3-12 v: vvvvvvvvv".parse().unwrap();
-        let validation = validate_letter_count(&entry);
-        assert_eq!(validation, true);
+        let validation = LetterCount.is_valid(&entry);
+        assert!(validation);
 
         let entry: PasswordEntry = "3-12 v: zbvlbpxcrnvwjpwl".parse().unwrap();
-        let validation = validate_letter_count(&entry);
-        assert_eq!(validation, false);
+        let validation = LetterCount.is_valid(&entry);
+        assert!(!validation);
     }
 
     #[test]
@@ -141,7 +201,38 @@ mod tests {
         advent_of_code::init();
 
         let entry: PasswordEntry = "3-12 v: xxvxxxxxxxxxxxxx".parse().unwrap();
-        let validation = validate_letter_position(&entry);
-        assert_eq!(validation, true);
+        let validation = LetterPosition.is_valid(&entry);
+        assert!(validation);
+
+        let entry: PasswordEntry = "3-12 v: xxvxxxxxxxxvxxxx".parse().unwrap();
+        assert!(!LetterPosition.is_valid(&entry));
+    }
+
+    #[test]
+    fn test_validate_letter_position_out_of_range() {
+        advent_of_code::init();
+
+        for line in &["0-2 a: ab", "1-3 a: ab", "5-9 a: ab", "1-2 a: a"] {
+            let entry: PasswordEntry = line.parse().unwrap();
+            assert!(
+                !LetterPosition.is_valid(&entry),
+                "{} should be invalid",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_valid() {
+        advent_of_code::init();
+
+        let entries: Vec<PasswordEntry> =
+            advent_of_code::utils::decode_line("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc");
+
+        assert_eq!(count_valid(&entries, &LetterCount), 2);
+        assert_eq!(count_valid(&entries, &LetterPosition), 1);
+
+        let names: Vec<_> = POLICIES.iter().map(|policy| policy.name()).collect();
+        assert_eq!(names, vec!["count", "position"]);
     }
 }
@@ -1,39 +1,317 @@
+use advent_of_code::answer::Answer;
+
 static PROBLEM_NAME: &str = "2020-03";
 
+#[cfg(test)]
 const TREE: char = '#';
 
+/// The slopes that need to be checked for part 2.
+static SLOPES: &[Slope] = &[
+    Slope::new(1, 1),
+    Slope::new(3, 1),
+    Slope::new(5, 1),
+    Slope::new(7, 1),
+    Slope::new(1, 2),
+];
+
+/// The most times the grid can be repeated to draw a path.
+const MAX_RENDER_REPEATS: usize = 100;
+
 fn main() {
     advent_of_code::init();
 
     let input = advent_of_code::load_input(PROBLEM_NAME);
-    let lines: Vec<String> = advent_of_code::utils::decode_line(&input);
+    let grid: Grid = input
+        .parse()
+        .unwrap_or_else(|err| panic!("invalid grid: {}", err));
 
-    let trees = count_trees(&lines, 1, 3);
+    let trees = grid.count_trees(Slope::new(3, 1));
     log::info!("Part 1 = {}", trees);
 
-    let combinations = &[(1, 1), (1, 3), (1, 5), (1, 7), (2, 1)];
-    let trees: u64 = combinations
-        .iter()
-        .map(|(down_by, right_by)| count_trees(&lines, *down_by, *right_by))
-        .product();
+    let trees = Answer::product(SLOPES.iter().map(|slope| grid.count_trees(*slope)));
     log::info!("Part 2 = {}", trees);
+
+    // A slope to draw can be provided as the first argument, such as `3`, `-1`,
+    // or `1/2`. Use `-` to skip drawing.
+    match advent_of_code::utils::arg::<String>(0).as_deref() {
+        None | Some("-") => (),
+        Some(slope) => {
+            let slope: Slope = slope.parse().unwrap_or_else(|err| {
+                log::error!("Invalid slope {}: {}", slope, err);
+                std::process::exit(1);
+            });
+
+            let path = grid.path(slope);
+            log::info!("Slope {} hits {} trees", slope, path.trees);
+
+            match grid.render(&path) {
+                Ok(rendered) => print!("{}", rendered),
+                Err(err) => {
+                    log::error!("Unable to draw slope {}: {}", slope, err);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    // Every slope up to a size can be explored by providing the size as the
+    // second argument, such as `- 5` to explore without drawing.
+    if let Some(size) = advent_of_code::utils::arg::<usize>(1) {
+        let exploration = explore(&grid, Slope::range(size));
+
+        if let (Some(fewest), Some(most)) = (exploration.fewest(), exploration.most()) {
+            log::info!("Fewest trees = {} with slope {}", fewest.1, fewest.0);
+            log::info!("Most trees = {} with slope {}", most.1, most.0);
+        }
+    }
+}
+
+/// A map of open squares and trees, which repeats forever to the right.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Grid {
+    width: usize,
+    height: usize,
+    /// If each square is a tree, row by row.
+    trees: Vec<bool>,
+}
+
+impl std::str::FromStr for Grid {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut width = None;
+        let mut height = 0;
+        let mut trees = Vec::new();
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if *width.get_or_insert(line.len()) != line.len() {
+                return Err("grid rows must all be the same width");
+            }
+
+            for square in line.chars() {
+                match square {
+                    '.' => trees.push(false),
+                    '#' => trees.push(true),
+                    _ => return Err("grid may only contain . and #"),
+                }
+            }
+
+            height += 1;
+        }
+
+        let width = width.ok_or("grid is empty")?;
+
+        Ok(Self {
+            width,
+            height,
+            trees,
+        })
+    }
+}
+
+impl Grid {
+    /// Determine if there is a tree at a position, wrapping horizontally.
+    fn is_tree(&self, x: usize, y: usize) -> bool {
+        self.trees[y * self.width + x % self.width]
+    }
+
+    /// Follow a slope from the top left corner to the bottom of the grid.
+    fn path(&self, slope: Slope) -> Path {
+        // Only the distance moved within a single copy of the grid matters, and
+        // reducing it first means large slopes can't overflow.
+        let right = slope.right.rem_euclid(self.width as i64) as usize;
+
+        let visited: Vec<_> = (0..self.height)
+            .step_by(slope.down)
+            .enumerate()
+            .map(|(step, y)| ((step * right) % self.width, y))
+            .collect();
+
+        let trees = visited.iter().filter(|(x, y)| self.is_tree(*x, *y)).count() as u64;
+
+        Path {
+            slope,
+            visited,
+            trees,
+        }
+    }
+
+    /// Count the number of trees encountered when following a slope.
+    fn count_trees(&self, slope: Slope) -> u64 {
+        self.path(slope).trees
+    }
+
+    /// Draw the grid with a path on it, where visited open squares are `O` and
+    /// visited trees are `X`. The grid is repeated enough times to show the
+    /// whole path without wrapping, up to [`MAX_RENDER_REPEATS`] times.
+    fn render(&self, path: &Path) -> Result<String, &'static str> {
+        const TOO_WIDE: &str = "path is too wide to draw";
+
+        let steps = path.visited.len().saturating_sub(1) as u64;
+        let distance = path
+            .slope
+            .right
+            .unsigned_abs()
+            .checked_mul(steps)
+            .ok_or(TOO_WIDE)?;
+        let repeats = distance.div_ceil(self.width as u64) + 1;
+        if repeats > MAX_RENDER_REPEATS as u64 {
+            return Err(TOO_WIDE);
+        }
+        let repeats = repeats as usize;
+
+        // Paths going left start from the right edge of the repeated grid.
+        let offset = if path.slope.right < 0 {
+            (repeats - 1) * self.width
+        } else {
+            0
+        };
+
+        let mut rows: Vec<Vec<char>> = (0..self.height)
+            .map(|y| {
+                (0..self.width * repeats)
+                    .map(|x| if self.is_tree(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+
+        for (step, (_x, y)) in path.visited.iter().enumerate() {
+            let x = (offset as i64 + step as i64 * path.slope.right) as usize;
+            let square = &mut rows[*y][x];
+            *square = if *square == '#' { 'X' } else { 'O' };
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect())
+    }
+}
+
+/// How far to move for each step down the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Slope {
+    /// How many squares to move right, or left if negative.
+    right: i64,
+    /// How many rows to move down. Must not be 0.
+    down: usize,
+}
+
+impl Slope {
+    const fn new(right: i64, down: usize) -> Self {
+        Self { right, down }
+    }
+
+    /// Every slope moving at most `size` squares left or right and between 1
+    /// and `size` rows down.
+    fn range(size: usize) -> impl Iterator<Item = Slope> {
+        let size = size.max(1);
+
+        (1..=size).flat_map(move |down| {
+            (-(size as i64)..=size as i64).map(move |right| Slope::new(right, down))
+        })
+    }
+}
+
+impl std::str::FromStr for Slope {
+    type Err = &'static str;
+
+    /// Parse a slope as a number of squares to the right for each row, written
+    /// as a whole number or a fraction. A fraction such as `1/2` moves one
+    /// square right for every two rows.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (right, down) = match s.split_once('/') {
+            Some((right, down)) => (right, down),
+            None => (s, "1"),
+        };
+
+        let right = right
+            .trim()
+            .parse()
+            .map_err(|_| "slope must move right by a number")?;
+        let down = down
+            .trim()
+            .parse()
+            .map_err(|_| "slope must move down by a number")?;
+
+        if down == 0 {
+            return Err("slope must move down");
+        }
+
+        Ok(Self::new(right, down))
+    }
+}
+
+impl std::fmt::Display for Slope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.down == 1 {
+            write!(f, "{}", self.right)
+        } else {
+            write!(f, "{}/{}", self.right, self.down)
+        }
+    }
+}
+
+/// The squares visited while following a slope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Path {
+    slope: Slope,
+    /// Each visited position as `(x, y)`, with `x` wrapped into the grid.
+    visited: Vec<(usize, usize)>,
+    trees: u64,
+}
+
+/// The number of trees hit by each explored slope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Exploration {
+    results: Vec<(Slope, u64)>,
+}
+
+impl Exploration {
+    /// The first slope hitting the fewest trees.
+    fn fewest(&self) -> Option<(Slope, u64)> {
+        self.results
+            .iter()
+            .copied()
+            .min_by_key(|(_slope, trees)| *trees)
+    }
+
+    /// The first slope hitting the most trees.
+    fn most(&self) -> Option<(Slope, u64)> {
+        self.results
+            .iter()
+            .copied()
+            .rev()
+            .max_by_key(|(_slope, trees)| *trees)
+    }
+}
+
+/// Count the trees hit by every slope.
+fn explore(grid: &Grid, slopes: impl Iterator<Item = Slope>) -> Exploration {
+    let results = slopes
+        .map(|slope| (slope, grid.count_trees(slope)))
+        .collect();
+
+    Exploration { results }
 }
 
 /// Count the number of trees encountered when stepping down and to the right
-/// by specified amounts.
+/// by specified amounts. Used to check [`Grid::count_trees`].
+#[cfg(test)]
 fn count_trees(lines: &[String], down_by: usize, right_by: usize) -> u64 {
     let mut trees = 0;
-    let mut index = 0;
 
     // Go through each line, skipping lines as needed.
-    for line in lines.iter().step_by(down_by) {
+    for (index, line) in lines.iter().step_by(down_by).enumerate() {
         // Calculate the distance to the right, wrapping based on the length of
         // the line. Then get the character at the needed index.
         let right = (index * right_by) % line.len();
         let value = line.chars().nth(right).unwrap();
 
-        index += 1;
-
         if value == TREE {
             trees += 1;
         }
@@ -66,4 +344,180 @@ mod tests {
         let trees = count_trees(&lines, 1, 3);
         assert_eq!(trees, 7);
     }
+
+    #[test]
+    fn test_grid_from_str() {
+        advent_of_code::init();
+
+        let grid: Grid = TEST_INPUT.parse().unwrap();
+        assert_eq!((grid.width, grid.height), (11, 11));
+        assert!(grid.is_tree(2, 0));
+        assert!(!grid.is_tree(0, 0));
+        assert!(grid.is_tree(13, 0));
+
+        assert_eq!("..\n.#\n\n".parse::<Grid>().unwrap().height, 2);
+        assert_eq!("".parse::<Grid>(), Err("grid is empty"));
+        assert_eq!(
+            "..\n...".parse::<Grid>(),
+            Err("grid rows must all be the same width")
+        );
+        assert_eq!(
+            "..\n.x".parse::<Grid>(),
+            Err("grid may only contain . and #")
+        );
+    }
+
+    #[test]
+    fn test_grid_count_trees() {
+        advent_of_code::init();
+
+        let grid: Grid = TEST_INPUT.parse().unwrap();
+        let lines: Vec<String> = advent_of_code::utils::decode_line(TEST_INPUT);
+
+        let expected = &[2, 7, 3, 4, 2];
+        for (slope, expected) in SLOPES.iter().zip(expected) {
+            assert_eq!(grid.count_trees(*slope), *expected, "{}", slope);
+            assert_eq!(
+                grid.count_trees(*slope),
+                count_trees(&lines, slope.down, slope.right as usize)
+            );
+        }
+
+        let trees: u64 = SLOPES
+            .iter()
+            .map(|slope| grid.count_trees(*slope))
+            .product();
+        assert_eq!(trees, 336);
+    }
+
+    #[test]
+    fn test_slope_from_str() {
+        advent_of_code::init();
+
+        assert_eq!("3".parse(), Ok(Slope::new(3, 1)));
+        assert_eq!("-1".parse(), Ok(Slope::new(-1, 1)));
+        assert_eq!("1/2".parse(), Ok(Slope::new(1, 2)));
+        assert_eq!("-3/4".parse(), Ok(Slope::new(-3, 4)));
+        assert_eq!("1/0".parse::<Slope>(), Err("slope must move down"));
+        assert_eq!(
+            "a".parse::<Slope>(),
+            Err("slope must move right by a number")
+        );
+        assert_eq!(
+            "1/-2".parse::<Slope>(),
+            Err("slope must move down by a number")
+        );
+
+        for slope in &["3", "-1", "1/2", "-3/4"] {
+            assert_eq!(slope.parse::<Slope>().unwrap().to_string(), *slope);
+        }
+    }
+
+    #[test]
+    fn test_path() {
+        advent_of_code::init();
+
+        let grid: Grid = TEST_INPUT.parse().unwrap();
+
+        let path = grid.path(Slope::new(1, 2));
+        assert_eq!(
+            path.visited,
+            vec![(0, 0), (1, 2), (2, 4), (3, 6), (4, 8), (5, 10)]
+        );
+
+        // Moving left wraps around to the right edge.
+        let path = grid.path(Slope::new(-3, 1));
+        assert_eq!(&path.visited[..4], &[(0, 0), (8, 1), (5, 2), (2, 3)]);
+        assert_eq!(path.trees, 3);
+
+        let path = grid.path(Slope::new(0, 1));
+        assert!(path.visited.iter().all(|(x, _y)| *x == 0));
+        assert_eq!(path.trees, 3);
+
+        // Huge slopes behave like the same slope within a single grid.
+        let path = grid.path(Slope::new(i64::MAX, 1));
+        assert_eq!(
+            path.visited,
+            grid.path(Slope::new(i64::MAX % 11, 1)).visited
+        );
+        let path = grid.path(Slope::new(i64::MIN, 1));
+        assert_eq!(
+            path.visited,
+            grid.path(Slope::new(i64::MIN % 11 + 11, 1)).visited
+        );
+    }
+
+    #[test]
+    fn test_explore() {
+        advent_of_code::init();
+
+        let grid: Grid = TEST_INPUT.parse().unwrap();
+
+        let slopes: Vec<_> = Slope::range(1).collect();
+        assert_eq!(
+            slopes,
+            vec![Slope::new(-1, 1), Slope::new(0, 1), Slope::new(1, 1)]
+        );
+
+        let exploration = explore(&grid, SLOPES.iter().copied());
+        assert_eq!(exploration.fewest(), Some((Slope::new(1, 1), 2)));
+        assert_eq!(exploration.most(), Some((Slope::new(3, 1), 7)));
+
+        let exploration = explore(&grid, Slope::range(3));
+        assert_eq!(exploration.results.len(), 21);
+        assert!(exploration
+            .results
+            .iter()
+            .all(|(slope, trees)| grid.count_trees(*slope) == *trees));
+
+        assert_eq!(explore(&grid, std::iter::empty()).most(), None);
+    }
+
+    #[test]
+    fn test_render() {
+        advent_of_code::init();
+
+        let grid: Grid = "..#\n#..\n.#.".parse().unwrap();
+
+        let path = grid.path(Slope::new(1, 1));
+        assert_eq!(grid.render(&path).unwrap(), "O.#..#\n#O.#..\n.#O.#.\n");
+
+        let path = grid.path(Slope::new(2, 1));
+        assert_eq!(
+            grid.render(&path).unwrap(),
+            "O.#..#..#\n#.O#..#..\n.#..X..#.\n"
+        );
+
+        let path = grid.path(Slope::new(-1, 2));
+        assert_eq!(grid.render(&path).unwrap(), "..#O.#\n#..#..\n.#O.#.\n");
+
+        let path = grid.path(Slope::new(-2, 1));
+        assert_eq!(
+            grid.render(&path).unwrap(),
+            "..#..#O.#\n#..#O.#..\n.#O.#..#.\n"
+        );
+
+        // Paths that would need the grid repeated too many times aren't drawn.
+        let path = grid.path(Slope::new(i64::MAX, 1));
+        assert_eq!(grid.render(&path), Err("path is too wide to draw"));
+        let path = grid.path(Slope::new(i64::MIN, 1));
+        assert_eq!(grid.render(&path), Err("path is too wide to draw"));
+    }
+
+    #[test]
+    fn test_generated_input() {
+        advent_of_code::init();
+
+        for input in advent_of_code::generate::inputs(PROBLEM_NAME, 5, 100) {
+            let grid: Grid = input.parse().unwrap();
+
+            let lines: Vec<String> = advent_of_code::utils::decode_line(&input);
+            for slope in SLOPES {
+                assert_eq!(
+                    grid.count_trees(*slope),
+                    count_trees(&lines, slope.down, slope.right as usize)
+                );
+            }
+        }
+    }
 }
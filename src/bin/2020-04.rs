@@ -1,173 +1,232 @@
-use std::collections::{HashMap, HashSet};
-use std::iter::FromIterator;
+use std::collections::{BTreeMap, HashMap};
 
+use advent_of_code::schema::Schema;
 use lazy_static::lazy_static;
+use serde::Serialize;
 
 static PROBLEM_NAME: &str = "2020-04";
 
+/// The rules for every passport field, as understood by [`Schema`].
+static PASSPORT_SCHEMA: &str = "byr required range 1920-2002
+iyr required range 2010-2020
+eyr required range 2020-2030
+hgt required units 150-193cm 59-76in
+hcl required prefix # regex ^[0-9a-f]{6}$
+ecl required oneof amb blu brn gry grn hzl oth
+pid required regex ^[0-9]{9}$
+cid optional any";
+
 lazy_static! {
-    /// All of the fields required for each passport entry.
-    static ref REQUIRED_FIELDS: HashSet<&'static str> = vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"].into_iter().collect();
-    /// All known eye colors.
-    static ref EYE_COLORS: HashSet<&'static str> = vec!["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].into_iter().collect();
+    /// The default rules for passports.
+    static ref DEFAULT_SCHEMA: Schema = PASSPORT_SCHEMA.parse().unwrap();
 }
 
 fn main() {
     advent_of_code::init();
 
+    // A path to a file with different rules can be provided as the first
+    // argument, or `default` to use the normal rules.
+    let schema = match advent_of_code::utils::arg::<String>(0) {
+        Some(path) if path != "default" => {
+            Schema::load(&path).unwrap_or_else(|err| panic!("invalid schema: {}", err))
+        }
+        _ => DEFAULT_SCHEMA.clone(),
+    };
+
+    // A format for a validation report can be provided as the second argument.
+    let report_format = advent_of_code::utils::arg::<ReportFormat>(1);
+
     let input = advent_of_code::load_input(PROBLEM_NAME);
     let passports = parse_passports(&input);
     log::debug!("Parsed {} passports", passports.len());
 
     let valid_passports: u32 = passports
         .iter()
-        .map(validate_fields)
+        .map(|passport| validate_fields(&schema, passport))
         .map(|valid| valid as u32)
         .sum();
     log::info!("Part 1 = {}", valid_passports);
 
     let valid_passports: u32 = passports
         .iter()
-        .map(|passport| validate_fields(&passport) && validate_contents(&passport))
+        .map(|passport| validate_fields(&schema, passport) && validate_contents(&schema, passport))
         .map(|valid| valid as u32)
         .sum();
     log::info!("Part 2 = {}", valid_passports);
+
+    if let Some(format) = report_format {
+        let report = build_report(&schema, &passports);
+
+        match format {
+            ReportFormat::Text => print!("{}", report),
+            ReportFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("report should serialize")
+            ),
+        }
+    }
 }
 
-/// Validate a number by ensuring it is between a minimum and maximum value.
-fn validate_number(number: u32, minimum: u32, maximum: u32) -> bool {
-    number >= minimum && number <= maximum
+/// Ways a validation report can be displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReportFormat {
+    Text,
+    Json,
 }
 
-/// Parse passports into a vec of key value pairs.
-fn parse_passports(input: &str) -> Vec<HashMap<&str, &str>> {
-    // Storage for all of our parsed passports
-    let mut passports = Vec::new();
+impl std::str::FromStr for ReportFormat {
+    type Err = &'static str;
 
-    // Currently active passport entry
-    let mut passport = HashMap::new();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err("report format must be text or json"),
+        }
+    }
+}
 
-    // Iterate through each line. If the line is empty the entry is complete
-    // and needs to be saved. Otherwise, continue adding to existing passport
-    // entry.
-    for line in input.lines() {
-        let line = line.trim();
+/// A field with an invalid value, and why it was invalid.
+#[derive(Debug, Serialize)]
+struct InvalidField<'a> {
+    field: &'a str,
+    value: &'a str,
+    reason: String,
+}
 
-        if line.is_empty() {
-            log::trace!("Parsed new passport: {:?}", passport);
+/// Everything wrong with a single passport.
+#[derive(Debug, Serialize)]
+struct PassportReport<'a> {
+    /// The position of the passport in the input, starting at 1.
+    passport: usize,
+    valid: bool,
+    missing: Vec<&'a str>,
+    invalid: Vec<InvalidField<'a>>,
+}
 
-            passports.push(passport.clone());
-            passport = HashMap::new();
-            continue;
-        }
+/// Everything wrong with every passport, along with how many times each kind
+/// of problem happened.
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    passports: Vec<PassportReport<'a>>,
+    summary: BTreeMap<String, usize>,
+}
+
+/// Validate every passport, recording each missing or invalid field.
+fn build_report<'a>(schema: &'a Schema, passports: &[HashMap<&'a str, &'a str>]) -> Report<'a> {
+    let mut summary = BTreeMap::new();
 
-        let fields = line.split(' ');
-        for field in fields {
-            if field.is_empty() {
-                continue;
+    let passports = passports
+        .iter()
+        .enumerate()
+        .map(|(index, passport)| {
+            let missing = schema.missing_fields(passport);
+            let invalid = schema.invalid_fields(passport);
+
+            for field in &missing {
+                *summary.entry(format!("missing {}", field)).or_default() += 1;
+            }
+
+            // Summaries are counted by the kind of problem instead of the
+            // exact message, so different values with the same problem are
+            // grouped together.
+            for failure in &invalid {
+                *summary
+                    .entry(format!("{}: {}", failure.field, failure.reason))
+                    .or_default() += 1;
             }
 
-            let mut parts = field.split(':');
-            let key = parts.next().unwrap();
-            let value = parts.next().unwrap();
+            PassportReport {
+                passport: index + 1,
+                valid: missing.is_empty() && invalid.is_empty(),
+                missing,
+                invalid: invalid
+                    .iter()
+                    .map(|failure| InvalidField {
+                        field: failure.field,
+                        value: failure.value,
+                        reason: failure.message(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
 
-            passport.insert(key, value);
+    Report { passports, summary }
+}
+
+impl std::fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for passport in &self.passports {
+            let status = if passport.valid { "valid" } else { "invalid" };
+            writeln!(f, "Passport {}: {}", passport.passport, status)?;
+
+            if !passport.missing.is_empty() {
+                writeln!(f, "  missing: {}", passport.missing.join(", "))?;
+            }
+
+            for invalid in &passport.invalid {
+                writeln!(f, "  {}: {}", invalid.field, invalid.reason)?;
+            }
         }
-    }
 
-    log::trace!("Parsed new passport: {:?}", passport);
-    passports.push(passport);
+        let valid = self
+            .passports
+            .iter()
+            .filter(|passport| passport.valid)
+            .count();
+        writeln!(f, "{} of {} passports valid", valid, self.passports.len())?;
 
-    passports
-}
+        if !self.summary.is_empty() {
+            writeln!(f, "Problems:")?;
+        }
 
-/// Determine if the passport contains all of the required fields.
-fn validate_fields(passport: &HashMap<&str, &str>) -> bool {
-    let fields: HashSet<&str> = HashSet::from_iter(passport.keys().into_iter().cloned());
-    let mut difference: HashSet<&str> = fields
-        .symmetric_difference(&REQUIRED_FIELDS)
-        .into_iter()
-        .cloned()
-        .collect();
-    difference.remove("cid");
+        for (reason, count) in &self.summary {
+            writeln!(f, "  {}: {}", reason, count)?;
+        }
+
+        Ok(())
+    }
+}
 
-    log::trace!("Difference between actual and required: {:?}", difference);
+/// Parse passports into a vec of key value pairs.
+///
+/// # Panics
+///
+/// This will panic if a passport contains a field that isn't a key value pair.
+fn parse_passports(input: &str) -> Vec<HashMap<&str, &str>> {
+    advent_of_code::utils::records(input)
+        .map(|record| {
+            let passport = advent_of_code::utils::fields(&record)
+                .unwrap_or_else(|err| panic!("invalid passport: {}", err));
+            log::trace!("Parsed new passport: {:?}", passport);
 
-    difference.is_empty()
+            passport
+        })
+        .collect()
 }
 
-/// Check if a character is a valid hexadecimal character.
-fn is_hex(c: char) -> bool {
-    match c {
-        'a'..='z' => true,
-        '0'..='9' => true,
-        _ => false,
-    }
+/// Determine if the passport contains all of the required fields and no fields
+/// that aren't in the schema.
+fn validate_fields(schema: &Schema, passport: &HashMap<&str, &str>) -> bool {
+    let missing = schema.missing_fields(passport);
+    log::trace!("Passport was missing fields: {:?}", missing);
+
+    let unknown = schema.unknown_fields(passport);
+    log::trace!("Passport had unknown fields: {:?}", unknown);
+
+    missing.is_empty() && unknown.is_empty()
 }
 
 /// Determine if all the fields within the passport contain valid data.
-fn validate_contents(passport: &HashMap<&str, &str>) -> bool {
-    for (key, value) in passport {
-        let valid = match *key {
-            "byr" => match value.parse() {
-                Ok(year) => validate_number(year, 1920, 2002),
-                _ => false,
-            },
-            "iyr" => match value.parse() {
-                Ok(year) => validate_number(year, 2010, 2020),
-                _ => false,
-            },
-            "eyr" => match value.parse() {
-                Ok(year) => validate_number(year, 2020, 2030),
-                _ => false,
-            },
-            "hgt" => {
-                let len = value.len();
-
-                if let Ok(num) = value[..len - 2].parse() {
-                    let units = &value[len - 2..];
-
-                    match units {
-                        "cm" => validate_number(num, 150, 193),
-                        "in" => validate_number(num, 59, 76),
-                        _ => false,
-                    }
-                } else {
-                    false
-                }
-            }
-            "hcl" => {
-                if value.len() == 7 {
-                    value.chars().skip(1).all(is_hex)
-                } else {
-                    false
-                }
-            }
-            "ecl" => EYE_COLORS.contains(value),
-            "pid" => {
-                if value.len() == 9 {
-                    value.parse::<u32>().is_ok()
-                } else {
-                    false
-                }
-            }
-            "cid" => true,
-            _ => unreachable!("all fields should have been validated"),
-        };
-
-        log::trace!("Field {} with data {} validity: {}", key, value, valid);
-
-        if !valid {
-            log::debug!(
-                "Passport was not valid because field {} contained data {}",
-                key,
-                value
-            );
-            return false;
-        }
+fn validate_contents(schema: &Schema, passport: &HashMap<&str, &str>) -> bool {
+    let invalid = schema.invalid_fields(passport);
+
+    for failure in &invalid {
+        log::debug!("Passport was not valid because field {}", failure);
     }
 
-    true
+    invalid.is_empty()
 }
 
 #[cfg(test)]
@@ -200,14 +259,188 @@ hcl:#623a2f";
 
         let passports = parse_passports(TEST_INPUT);
         assert_eq!(passports.len(), 4);
+        assert_eq!(passports[0]["hgt"], "183cm");
+
+        let input = TEST_INPUT.replace('\n', "\r\n");
+        let input = format!("\r\n{}\r\n\r\n  \r\n", input);
+        assert_eq!(parse_passports(&input), passports);
     }
 
     #[test]
-    fn test_is_hex() {
+    fn test_passport_schema() {
         advent_of_code::init();
 
-        assert_eq!(is_hex('a'), true);
-        assert_eq!(is_hex('.'), false);
+        let schema = &DEFAULT_SCHEMA;
+        assert_eq!(schema.fields().len(), 8);
+        assert!(schema
+            .fields()
+            .iter()
+            .all(|field| field.required || field.name == "cid"));
+
+        let hcl = &schema.field("hcl").unwrap().validator;
+        assert!(hcl.validate("#123abc").is_ok());
+        assert!(hcl.validate("#123abz").is_err());
+        assert!(hcl.validate("123abc").is_err());
+    }
+
+    /// Check a value against the default rules for a field.
+    fn is_valid(field: &str, value: &str) -> bool {
+        DEFAULT_SCHEMA
+            .field(field)
+            .unwrap()
+            .validator
+            .validate(value)
+            .is_ok()
+    }
+
+    #[test]
+    fn test_year_boundaries() {
+        advent_of_code::init();
+
+        for (field, min, max) in &[
+            ("byr", 1920, 2002),
+            ("iyr", 2010, 2020),
+            ("eyr", 2020, 2030),
+        ] {
+            assert!(!is_valid(field, &(min - 1).to_string()));
+            assert!(is_valid(field, &min.to_string()));
+            assert!(is_valid(field, &max.to_string()));
+            assert!(!is_valid(field, &(max + 1).to_string()));
+
+            // Years must be exactly four digits.
+            assert!(!is_valid(field, &format!("0{}", min)));
+            assert!(!is_valid(field, &format!("+{}", min)));
+            assert!(!is_valid(field, &format!("{} ", min)));
+            assert!(!is_valid(field, ""));
+        }
+    }
+
+    #[test]
+    fn test_height_boundaries() {
+        advent_of_code::init();
+
+        for (unit, min, max) in &[("cm", 150, 193), ("in", 59, 76)] {
+            assert!(!is_valid("hgt", &format!("{}{}", min - 1, unit)));
+            assert!(is_valid("hgt", &format!("{}{}", min, unit)));
+            assert!(is_valid("hgt", &format!("{}{}", max, unit)));
+            assert!(!is_valid("hgt", &format!("{}{}", max + 1, unit)));
+            assert!(!is_valid("hgt", &format!("+{}{}", min, unit)));
+            assert!(!is_valid("hgt", &format!("0{}{}", min, unit)));
+        }
+
+        for value in &[
+            "190", "cm", "in", "", "c", "1", "190CM", "60in ", "6é", "é", "日本",
+        ] {
+            assert!(!is_valid("hgt", value), "{} should be invalid", value);
+        }
+    }
+
+    #[test]
+    fn test_hair_color_boundaries() {
+        advent_of_code::init();
+
+        assert!(is_valid("hcl", "#123abc"));
+        assert!(is_valid("hcl", "#000000"));
+        assert!(is_valid("hcl", "#ffffff"));
+
+        for value in &[
+            "#zzzzzz", "#123abz", "#ABCDEF", "123abc", "#12345", "#1234567", "#", "", "#12345é",
+        ] {
+            assert!(!is_valid("hcl", value), "{} should be invalid", value);
+        }
+    }
+
+    #[test]
+    fn test_eye_color_boundaries() {
+        advent_of_code::init();
+
+        for value in &["amb", "blu", "brn", "gry", "grn", "hzl", "oth"] {
+            assert!(is_valid("ecl", value));
+        }
+
+        for value in &["wat", "AMB", "amb ", "", "am"] {
+            assert!(!is_valid("ecl", value), "{} should be invalid", value);
+        }
+    }
+
+    #[test]
+    fn test_passport_id_boundaries() {
+        advent_of_code::init();
+
+        assert!(is_valid("pid", "000000001"));
+        assert!(is_valid("pid", "999999999"));
+
+        for value in &[
+            "0123456789",
+            "12345678",
+            "+12345678",
+            "-12345678",
+            "12345678a",
+            "١٢٣٤٥٦٧٨٩",
+            "",
+        ] {
+            assert!(!is_valid("pid", value), "{} should be invalid", value);
+        }
+
+        assert!(is_valid("cid", ""));
+        assert!(is_valid("cid", "anything"));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_validate_contents_never_panics(
+            passport in proptest::collection::hash_map(
+                "byr|iyr|eyr|hgt|hcl|ecl|pid|cid|\\PC{0,4}",
+                "\\PC{0,12}|[#0-9a-z]{0,8}|[0-9]{1,12}(cm|in)?",
+                0..10,
+            )
+        ) {
+            let passport: HashMap<&str, &str> = passport
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect();
+
+            // Only checking that validation never panics.
+            validate_fields(&DEFAULT_SCHEMA, &passport);
+            validate_contents(&DEFAULT_SCHEMA, &passport);
+            build_report(&DEFAULT_SCHEMA, &[passport]);
+        }
+    }
+
+    #[test]
+    fn test_build_report() {
+        advent_of_code::init();
+
+        let input = format!(
+            "{}\n\n{}\n\nhgt:190 hcl:123abc",
+            VALID_PASSPORT, INVALID_PASSPORT
+        );
+        let passports = parse_passports(&input);
+        let report = build_report(&DEFAULT_SCHEMA, &passports);
+
+        assert!(report.passports[0].valid);
+        assert!(!report.passports[1].valid);
+
+        let last = &report.passports[2];
+        assert_eq!(last.missing, vec!["byr", "iyr", "eyr", "ecl", "pid"]);
+        let reasons: Vec<_> = last
+            .invalid
+            .iter()
+            .map(|invalid| format!("{}: {}", invalid.field, invalid.reason))
+            .collect();
+        assert_eq!(reasons, vec!["hcl: missing #", "hgt: 190 has no unit"]);
+
+        // The second passport also has a height without a unit.
+        assert_eq!(report.summary["hgt: no unit"], 2);
+        assert_eq!(report.summary["missing byr"], 1);
+
+        let text = report.to_string();
+        assert!(text.contains("Passport 3: invalid\n  missing: byr, iyr, eyr, ecl, pid\n"));
+        assert!(text.contains("1 of 3 passports valid"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["passports"][2]["invalid"][0]["reason"], "missing #");
+        assert_eq!(json["summary"]["hgt: no unit"], 2);
     }
 
     #[test]
@@ -216,8 +449,14 @@ hcl:#623a2f";
 
         let passports = parse_passports(TEST_INPUT);
 
-        assert_eq!(validate_fields(&passports[0]), true);
-        assert_eq!(validate_fields(&passports[1]), false);
+        assert!(validate_fields(&DEFAULT_SCHEMA, &passports[0]));
+        assert!(!validate_fields(&DEFAULT_SCHEMA, &passports[1]));
+
+        // Fields that aren't in the schema are rejected, even if every required
+        // field is present.
+        let input = format!("{} xyz:1", VALID_PASSPORT);
+        let passports = parse_passports(&input);
+        assert!(!validate_fields(&DEFAULT_SCHEMA, &passports[0]));
     }
 
     #[test]
@@ -225,9 +464,9 @@ hcl:#623a2f";
         advent_of_code::init();
 
         let passports = parse_passports(VALID_PASSPORT);
-        assert_eq!(validate_contents(&passports[0]), true);
+        assert!(validate_contents(&DEFAULT_SCHEMA, &passports[0]));
 
         let passports = parse_passports(INVALID_PASSPORT);
-        assert_eq!(validate_contents(&passports[0]), false);
+        assert!(!validate_contents(&DEFAULT_SCHEMA, &passports[0]));
     }
 }
@@ -1,25 +1,184 @@
 static PROBLEM_NAME: &str = "2020-05";
 
-const ROW_WIDTH: usize = 8;
-const ROWS: usize = 127;
-const COLUMNS: usize = 7;
+/// The layout of the plane from the puzzle, with 128 rows and 8 columns.
+const DEFAULT_GEOMETRY: Geometry = Geometry {
+    row_bits: 7,
+    column_bits: 3,
+};
+
+/// The most seats that will be drawn in a seat map.
+const MAX_RENDER_SEATS: usize = 1 << 16;
 
 fn main() {
     advent_of_code::init();
 
+    // The number of characters used for rows and columns can be provided as
+    // the first and second arguments.
+    let geometry = Geometry::new(
+        advent_of_code::utils::arg(0).unwrap_or(DEFAULT_GEOMETRY.row_bits),
+        advent_of_code::utils::arg(1).unwrap_or(DEFAULT_GEOMETRY.column_bits),
+    )
+    .unwrap_or_else(|err| {
+        log::error!("Invalid plane geometry: {}", err);
+        std::process::exit(1);
+    });
+
     let input = advent_of_code::load_input(PROBLEM_NAME);
-    let seats: Vec<String> = advent_of_code::utils::decode_line(&input);
-
-    let highest_seat_id = seats
-        .iter()
-        .map(|seat| decode_seat(&seat))
-        .map(|(row, column)| seat_id(row, column))
-        .max()
-        .unwrap();
+    let passes: Vec<BoardingPass> = input
+        .lines()
+        .map(|line| {
+            geometry
+                .parse(line)
+                .unwrap_or_else(|err| panic!("{} is not a valid boarding pass: {}", line, err))
+        })
+        .collect();
+
+    let seat_ids: Vec<usize> = passes.iter().map(BoardingPass::seat_id).collect();
+
+    let highest_seat_id = seat_ids.iter().max().unwrap();
     log::info!("Part 1 = {}", highest_seat_id);
 
-    let missing_seat_id = find_missing_value(&seats);
+    let missing_seat_id = find_missing_seat(&seat_ids).expect("unable to find missing seat");
     log::info!("Part 2 = {}", missing_seat_id);
+
+    if log::log_enabled!(log::Level::Debug) {
+        match render_seat_map(geometry, &passes) {
+            Ok(map) => log::debug!("Seat map:\n{}", map),
+            Err(err) => log::debug!("Not drawing seat map: {}", err),
+        }
+    }
+}
+
+/// The layout of seats in a plane, described by how many characters of a
+/// boarding pass are used for the row and column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Geometry {
+    row_bits: u32,
+    column_bits: u32,
+}
+
+impl Geometry {
+    /// Create a new geometry, ensuring every seat ID can be represented.
+    fn new(row_bits: u32, column_bits: u32) -> Result<Self, &'static str> {
+        if row_bits.saturating_add(column_bits) > 32 {
+            return Err("rows and columns must use at most 32 characters in total");
+        }
+
+        Ok(Self {
+            row_bits,
+            column_bits,
+        })
+    }
+
+    /// The number of rows in the plane.
+    fn rows(&self) -> usize {
+        1 << self.row_bits
+    }
+
+    /// The number of seats in each row.
+    fn columns(&self) -> usize {
+        1 << self.column_bits
+    }
+
+    /// Convert a row and column into a seat ID.
+    fn seat_id(&self, row: usize, column: usize) -> usize {
+        row * self.columns() + column
+    }
+
+    /// Parse a boarding pass with this geometry.
+    fn parse(&self, s: &str) -> Result<BoardingPass, &'static str> {
+        // The puzzle's layout can be decoded directly, only falling back to the
+        // slower path to find out what was wrong with an invalid pass.
+        if *self == DEFAULT_GEOMETRY {
+            if let Some(id) = decode_seat_id(s) {
+                let id = usize::from(id);
+
+                return Ok(BoardingPass {
+                    row: id >> self.column_bits,
+                    column: id & (self.columns() - 1),
+                    geometry: *self,
+                });
+            }
+        }
+
+        self.parse_letters(s)
+    }
+
+    /// Parse a boarding pass one letter at a time, which works for any
+    /// geometry and describes what was wrong with invalid passes.
+    fn parse_letters(&self, s: &str) -> Result<BoardingPass, &'static str> {
+        let row_len = self.row_bits as usize;
+
+        if s.len() != row_len + self.column_bits as usize {
+            return Err("boarding pass has the wrong number of characters");
+        }
+
+        // The length was checked in bytes, so any multibyte characters would
+        // be rejected below before this split could land inside of one.
+        if !s.is_char_boundary(row_len) {
+            return Err("row must only contain F or B");
+        }
+        let (row, column) = s.split_at(row_len);
+
+        if !row.chars().all(|c| c == 'F' || c == 'B') {
+            return Err("row must only contain F or B");
+        }
+
+        if !column.chars().all(|c| c == 'L' || c == 'R') {
+            return Err("column must only contain L or R");
+        }
+
+        Ok(BoardingPass {
+            row: binary_space_partition(row, self.rows() - 1),
+            column: binary_space_partition(column, self.columns() - 1),
+            geometry: *self,
+        })
+    }
+}
+
+/// A seat in a plane.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct BoardingPass {
+    row: usize,
+    column: usize,
+    geometry: Geometry,
+}
+
+impl BoardingPass {
+    /// The ID of the seat.
+    fn seat_id(&self) -> usize {
+        self.geometry.seat_id(self.row, self.column)
+    }
+}
+
+impl std::str::FromStr for BoardingPass {
+    type Err = &'static str;
+
+    /// Parse a boarding pass for the default plane geometry.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DEFAULT_GEOMETRY.parse(s)
+    }
+}
+
+impl std::fmt::Display for BoardingPass {
+    /// Encode the seat back into its letters, such as `FBFBBFFRLR`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for bit in (0..self.geometry.row_bits).rev() {
+            let c = if self.row >> bit & 1 == 1 { 'B' } else { 'F' };
+            write!(f, "{}", c)?;
+        }
+
+        for bit in (0..self.geometry.column_bits).rev() {
+            let c = if self.column >> bit & 1 == 1 {
+                'R'
+            } else {
+                'L'
+            };
+            write!(f, "{}", c)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// Perform a binary space partition from 0 to space. The letters 'B' and 'R' go
@@ -29,7 +188,7 @@ fn binary_space_partition(seat: &str, space: usize) -> usize {
     // the characters to 0s and 1s and get it as a binary representation. I've
     // left it this way as it was my original approach.
     let mut num = 0;
-    let mut step = (space + 1) / 2;
+    let mut step = space.div_ceil(2);
 
     for c in seat.chars() {
         match c {
@@ -37,65 +196,140 @@ fn binary_space_partition(seat: &str, space: usize) -> usize {
             _ => (),
         }
 
-        step = step / 2;
+        step /= 2;
     }
 
     num
 }
 
-/// Decode seat letters into a row and column.
-fn decode_seat(seat: &str) -> (usize, usize) {
-    let row = binary_space_partition(&seat[..7], ROWS);
-    let column = binary_space_partition(&seat[7..], COLUMNS);
+/// Decode a boarding pass for the default plane geometry directly into its seat
+/// ID, treating the letters as bits of a binary number.
+///
+/// Returns `None` if the pass isn't 7 row letters followed by 3 column letters.
+fn decode_seat_id(seat: &str) -> Option<u16> {
+    let bytes = seat.as_bytes();
+    if bytes.len() != 10 {
+        return None;
+    }
 
-    (row, column)
+    let mut id = 0;
+
+    for (index, byte) in bytes.iter().enumerate() {
+        let valid = if index < 7 {
+            matches!(byte, b'F' | b'B')
+        } else {
+            matches!(byte, b'L' | b'R')
+        };
+
+        if !valid {
+            return None;
+        }
+
+        // Of the four letters, only 'B' (0x42) and 'R' (0x52) have the bit
+        // with a value of 4 unset.
+        id = id << 1 | u16::from(!byte >> 2 & 1);
+    }
+
+    Some(id)
 }
 
-/// Convert a row and column into a seat ID.
-fn seat_id(row: usize, column: usize) -> usize {
-    row * ROW_WIDTH + column
+/// Draw every seat in the plane, one line per row, with `#` for occupied seats
+/// and `.` for empty seats. Planes with more than [`MAX_RENDER_SEATS`] seats
+/// are not drawn.
+fn render_seat_map(geometry: Geometry, passes: &[BoardingPass]) -> Result<String, &'static str> {
+    if geometry.rows().saturating_mul(geometry.columns()) > MAX_RENDER_SEATS {
+        return Err("plane has too many seats to draw");
+    }
+
+    let columns = geometry.columns();
+    let mut occupied = vec![false; geometry.rows() * columns];
+
+    for pass in passes {
+        occupied[geometry.seat_id(pass.row, pass.column)] = true;
+    }
+
+    let mut map = String::with_capacity(occupied.len() + geometry.rows());
+    for row in occupied.chunks(columns) {
+        map.extend(row.iter().map(|seat| if *seat { '#' } else { '.' }));
+        map.push('\n');
+    }
+
+    Ok(map)
 }
 
-/// Find an unoccupied seat where the seat is filled before and after it.
-fn find_missing_value(seats: &[String]) -> usize {
-    let mut seats: Vec<_> = seats
-        .iter()
-        .map(|seat| decode_seat(&seat))
-        .map(|(row, column)| seat_id(row, column))
-        .collect();
-    seats.sort();
-
-    // Go through each seat, ensure there is a seat above and below it, and if
-    // the above and below seats are not exactly 2 apart it means there's a
-    // variance which is our empty seat.
-    for (index, seat_id) in seats.iter().enumerate() {
-        if index > 0 {
-            if index < seats.len() - 1 {
-                if seats[index + 1] - seats[index - 1] != 2 {
-                    // We've found the ID of the seat directly before ours, so
-                    // we need to add 1 to get to our unused seat ID.
-                    return *seat_id + 1;
-                }
-            }
+/// Find an unoccupied seat where the seat is filled before and after it, in
+/// `O(n)` time without sorting.
+///
+/// Seat IDs must be unique and cover a contiguous range with exactly one seat
+/// missing from the middle, otherwise `None` is returned.
+fn find_missing_seat(seat_ids: &[usize]) -> Option<usize> {
+    let min = *seat_ids.iter().min()?;
+    let max = *seat_ids.iter().max()?;
+
+    // Every seat in the range except one should be present.
+    if max - min != seat_ids.len() {
+        return None;
+    }
+
+    // Mark each seat in the range as it's seen. With the right number of IDs,
+    // a duplicate means another seat must be missing as well.
+    let mut seen = vec![false; max - min + 1];
+    for id in seat_ids {
+        if std::mem::replace(&mut seen[id - min], true) {
+            return None;
+        }
+    }
+
+    seen.iter()
+        .position(|seen| !seen)
+        .map(|offset| min + offset)
+}
+
+/// Find an unoccupied seat where the seat is filled before and after it, by
+/// sorting every seat. Used to check [`find_missing_seat`].
+#[cfg(test)]
+fn find_missing_value(seat_ids: &[usize]) -> usize {
+    let mut seats = seat_ids.to_vec();
+    seats.sort_unstable();
+
+    // Go through each pair of neighboring seats, and if they are exactly 2
+    // apart it means the seat between them is our empty seat.
+    for pair in seats.windows(2) {
+        if pair[1] - pair[0] == 2 {
+            return pair[0] + 1;
         }
     }
 
     panic!("unable to find missing seat");
 }
 
+/// Decode seat letters into a row and column, without going through
+/// [`decode_seat_id`]. Used to check the faster path.
+#[cfg(test)]
+fn decode_seat(seat: &str) -> (usize, usize) {
+    let row = binary_space_partition(&seat[..7], 127);
+    let column = binary_space_partition(&seat[7..], 7);
+
+    (row, column)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_seat_id() {
         advent_of_code::init();
 
-        let id = seat_id(1, 1);
+        let id = DEFAULT_GEOMETRY.seat_id(1, 1);
         assert_eq!(id, 9);
 
-        let id = seat_id(8, 7);
+        let id = DEFAULT_GEOMETRY.seat_id(8, 7);
         assert_eq!(id, 71);
+
+        let pass: BoardingPass = "FBFBBFFRLR".parse().unwrap();
+        assert_eq!(pass.seat_id(), 357);
     }
 
     #[test]
@@ -110,24 +344,172 @@ mod tests {
     }
 
     #[test]
-    fn test_decode_seat() {
+    fn test_boarding_pass_from_str() {
+        advent_of_code::init();
+
+        let pass: BoardingPass = "BFFFBBFRRR".parse().unwrap();
+        assert_eq!((pass.row, pass.column), (70, 7));
+
+        let pass: BoardingPass = "BBFFBBFRLL".parse().unwrap();
+        assert_eq!((pass.row, pass.column), (102, 4));
+
+        assert!("BFFFBBFRR".parse::<BoardingPass>().is_err());
+        assert!("BFFFBBFRRRR".parse::<BoardingPass>().is_err());
+        assert!("BFFXBBFRRR".parse::<BoardingPass>().is_err());
+        assert!("BFFFBBFRRF".parse::<BoardingPass>().is_err());
+        assert!("RLLRLRRRRL".parse::<BoardingPass>().is_err());
+        assert!("bfffbbfrrr".parse::<BoardingPass>().is_err());
+        assert!("BFFFBBéRR".parse::<BoardingPass>().is_err());
+        assert!("BFFFBBFéR".parse::<BoardingPass>().is_err());
+    }
+
+    #[test]
+    fn test_boarding_pass_display() {
+        advent_of_code::init();
+
+        for seat in &["FBFBBFFRLR", "BFFFBBFRRR", "FFFFFFFLLL", "BBBBBBBRRR"] {
+            let pass: BoardingPass = seat.parse().unwrap();
+            assert_eq!(pass.to_string(), *seat);
+        }
+
+        let geometry = Geometry::new(2, 1).unwrap();
+        let pass = geometry.parse("BFR").unwrap();
+        assert_eq!((pass.row, pass.column, pass.seat_id()), (2, 1, 5));
+        assert_eq!(pass.to_string(), "BFR");
+    }
+
+    #[test]
+    fn test_geometry() {
+        advent_of_code::init();
+
+        assert_eq!(DEFAULT_GEOMETRY.rows(), 128);
+        assert_eq!(DEFAULT_GEOMETRY.columns(), 8);
+        assert_eq!(Geometry::new(7, 3), Ok(DEFAULT_GEOMETRY));
+        assert!(Geometry::new(30, 3).is_err());
+
+        let geometry = Geometry::new(0, 2).unwrap();
+        let pass = geometry.parse("RL").unwrap();
+        assert_eq!(pass.seat_id(), 2);
+        assert!(geometry.parse("FRL").is_err());
+    }
+
+    #[test]
+    fn test_render_seat_map() {
         advent_of_code::init();
 
-        let seat = decode_seat("BFFFBBFRRR");
-        assert_eq!(seat, (70, 7));
+        let geometry = Geometry::new(2, 2).unwrap();
+        let passes: Vec<_> = ["FFLL", "FFRR", "BFLR", "BBRL"]
+            .iter()
+            .map(|seat| geometry.parse(seat).unwrap())
+            .collect();
+
+        assert_eq!(
+            render_seat_map(geometry, &passes).unwrap(),
+            "#..#\n....\n.#..\n..#.\n"
+        );
+
+        let geometry = Geometry::new(16, 16).unwrap();
+        assert!(render_seat_map(geometry, &[]).is_err());
     }
 
     #[test]
     fn test_find_missing_value() {
         advent_of_code::init();
 
-        let seats = &[
-            "RLLRLRRRRL".to_string(),
-            "RLLRLRRRRL".to_string(),
-            "RLLRRLLLLL".to_string(),
-            "RLLRRLLLLR".to_string(),
-        ];
-        let missing = find_missing_value(seats);
-        assert_eq!(missing, 609);
+        let seats: Vec<_> = ["BFFBFBBRRL", "BFFBFBBRRL", "BFFBBFFLLL", "BFFBBFFLLR"]
+            .iter()
+            .map(|seat| seat.parse::<BoardingPass>().unwrap().seat_id())
+            .collect();
+        let missing = find_missing_value(&seats);
+        assert_eq!(missing, 607);
+    }
+
+    #[test]
+    fn test_decode_seat_id() {
+        advent_of_code::init();
+
+        assert_eq!(decode_seat_id("FBFBBFFRLR"), Some(357));
+        assert_eq!(decode_seat_id("BFFFBBFRRR"), Some(567));
+        assert_eq!(decode_seat_id("FFFBBBFRRR"), Some(119));
+        assert_eq!(decode_seat_id("BBFFBBFRLL"), Some(820));
+        assert_eq!(decode_seat_id("FFFFFFFLLL"), Some(0));
+        assert_eq!(decode_seat_id("BBBBBBBRRR"), Some(1023));
+
+        assert_eq!(decode_seat_id("RLLRLRRRRL"), None);
+        assert_eq!(decode_seat_id("FBFBBFFRL"), None);
+        assert_eq!(decode_seat_id("FBFBBFFRLRR"), None);
+        assert_eq!(decode_seat_id("FBFBBFFRLé"), None);
+    }
+
+    #[test]
+    fn test_find_missing_seat() {
+        advent_of_code::init();
+
+        assert_eq!(find_missing_seat(&[5, 3, 7, 4]), Some(6));
+        assert_eq!(find_missing_seat(&[1, 3]), Some(2));
+
+        // No gap, a gap at the edge, or more than one gap.
+        assert_eq!(find_missing_seat(&[3, 4, 5]), None);
+        assert_eq!(find_missing_seat(&[3, 5, 6, 8]), None);
+        assert_eq!(find_missing_seat(&[3]), None);
+
+        // Duplicates are rejected, even when the number of IDs matches. Here
+        // 3 and 12 are missing and 7 is repeated, which XORs to 8.
+        assert_eq!(
+            find_missing_seat(&[2, 4, 5, 6, 7, 7, 8, 9, 10, 11, 13]),
+            None
+        );
+        assert_eq!(find_missing_seat(&[3, 3, 6]), None);
+        assert_eq!(find_missing_seat(&[]), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_decode_seat_id(seat in "[FB]{7}[LR]{3}") {
+            let (row, column) = decode_seat(&seat);
+            let id = decode_seat_id(&seat).unwrap();
+
+            prop_assert_eq!(usize::from(id), DEFAULT_GEOMETRY.seat_id(row, column));
+        }
+
+        #[test]
+        fn prop_decode_seat_id_invalid(seat in "[FBLR]{0,12}|\\PC{0,12}") {
+            let fast = decode_seat_id(&seat).map(usize::from);
+            let slow = DEFAULT_GEOMETRY.parse_letters(&seat).ok().map(|pass| pass.seat_id());
+
+            prop_assert_eq!(fast, slow);
+        }
+
+        #[test]
+        fn prop_find_missing_seat(
+            (seats, missing) in (0usize..1000, 3usize..200)
+                .prop_flat_map(|(start, len)| (Just(start..start + len), 1..len - 1))
+                .prop_flat_map(|(range, offset)| {
+                    let missing = range.start + offset;
+                    let seats: Vec<_> = range.filter(|seat| *seat != missing).collect();
+
+                    (Just(seats).prop_shuffle(), Just(missing))
+                })
+        ) {
+            prop_assert_eq!(find_missing_seat(&seats), Some(missing));
+            prop_assert_eq!(find_missing_value(&seats), missing);
+        }
+    }
+
+    #[test]
+    fn test_generated_input() {
+        advent_of_code::init();
+
+        for input in advent_of_code::generate::inputs(PROBLEM_NAME, 5, 500) {
+            let seat_ids: Vec<usize> = input
+                .lines()
+                .map(|line| DEFAULT_GEOMETRY.parse(line).unwrap().seat_id())
+                .collect();
+
+            assert_eq!(
+                find_missing_seat(&seat_ids),
+                Some(find_missing_value(&seat_ids))
+            );
+        }
     }
 }
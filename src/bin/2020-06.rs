@@ -1,5 +1,4 @@
-use std::collections::{HashMap, HashSet};
-use std::iter::FromIterator;
+use advent_of_code::letters::LetterSet;
 
 static PROBLEM_NAME: &str = "2020-06";
 
@@ -9,74 +8,95 @@ fn main() {
     let input = advent_of_code::load_input(PROBLEM_NAME);
     let groups = parse_groups(&input);
 
-    let unique_questions: usize = groups.iter().map(|group| unique_questions(&group)).sum();
+    let unique_questions: usize = groups.iter().map(|group| unique_questions(group)).sum();
     log::info!("Part 1 = {}", unique_questions);
 
-    let all_answered_yes: usize = groups.iter().map(|group| all_answered(&group)).sum();
+    let all_answered_yes: usize = groups.iter().map(|group| all_answered(group)).sum();
     log::info!("Part 2 = {}", all_answered_yes);
+
+    // Optionally count questions answered by at least some number of people in
+    // each group, provided as the first argument.
+    if let Some(people) = advent_of_code::utils::arg(0) {
+        let answered: usize = groups
+            .iter()
+            .map(|group| Aggregation::AtLeast(people).apply(group).len())
+            .sum();
+        log::info!("Answered by at least {} = {}", people, answered);
+    }
 }
 
 /// Parse a collection of groups into each group, each person, and each question
 /// the person answered yes to.
-fn parse_groups(input: &str) -> Vec<Vec<HashSet<char>>> {
-    let mut groups = Vec::new();
-    let mut group = Vec::new();
-
-    for line in input.lines() {
-        if line.trim().is_empty() {
-            groups.push(group.clone());
-            group = Vec::new();
-            continue;
-        }
-
-        group.push(HashSet::from_iter(line.chars()));
-    }
-
-    groups.push(group);
+///
+/// # Panics
+///
+/// This will panic if a person answered something other than a through z.
+fn parse_groups(input: &str) -> Vec<Vec<LetterSet>> {
+    advent_of_code::utils::records(input)
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|person| {
+                    person
+                        .parse()
+                        .unwrap_or_else(|err| panic!("invalid answers {}: {}", person, err))
+                })
+                .collect()
+        })
+        .collect()
+}
 
-    groups
+/// A way of combining everyone's answers within a group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Aggregation {
+    /// Questions anyone answered yes to.
+    Any,
+    /// Questions everyone answered yes to.
+    All,
+    /// Questions at least this many people answered yes to.
+    AtLeast(usize),
 }
 
-/// Calculate the number of unique questions asked within a group.
-fn unique_questions(answers: &[HashSet<char>]) -> usize {
-    // All we have to do is construct a HashSet, try to insert every question,
-    // then count how many elements we ended up with.
-    let mut questions = HashSet::new();
-
-    for group in answers {
-        for question in group {
-            questions.insert(question);
+impl Aggregation {
+    /// Find the questions in a group matching this aggregation.
+    fn apply(self, answers: &[LetterSet]) -> LetterSet {
+        match self {
+            Aggregation::Any => answers
+                .iter()
+                .fold(LetterSet::new(), |acc, person| acc.union(*person)),
+            Aggregation::All => answers
+                .iter()
+                .copied()
+                .reduce(LetterSet::intersection)
+                .unwrap_or_default(),
+            Aggregation::AtLeast(people) => ('a'..='z')
+                .filter(|question| {
+                    answers
+                        .iter()
+                        .filter(|person| person.contains(*question))
+                        .count()
+                        >= people
+                })
+                .collect(),
         }
     }
+}
 
-    questions.len()
+/// Calculate the number of unique questions asked within a group.
+fn unique_questions(answers: &[LetterSet]) -> usize {
+    Aggregation::Any.apply(answers).len()
 }
 
 /// Calculate the number of questions where everyone answered yes.
-fn all_answered(answers: &[HashSet<char>]) -> usize {
-    // Count the number of times each question has been answered within a group.
-    // Once everything is counted, find the number of items who have as many
-    // answers as there were people in the group.
-    let mut counts: HashMap<char, usize> = HashMap::new();
-
-    for group in answers {
-        for question in group {
-            let entry = counts.entry(*question).or_default();
-            *entry += 1;
-        }
-    }
-
-    counts
-        .iter()
-        .filter(|(_question, count)| **count == answers.len())
-        .count()
+fn all_answered(answers: &[LetterSet]) -> usize {
+    Aggregation::All.apply(answers).len()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    static TEST_INPUT: &'static str = "abc\n\na\nb\nc\n\nab\nac";
+    static TEST_INPUT: &str = "abc\n\na\nb\nc\n\nab\nac";
 
     #[test]
     fn test_parse_groups() {
@@ -86,7 +106,12 @@ mod tests {
         assert_eq!(groups.len(), 3);
         assert_eq!(groups[0].len(), 1);
         assert_eq!(groups[1].len(), 3);
-        assert_eq!(groups[0][0], vec!['a', 'b', 'c'].into_iter().collect());
+        assert_eq!(groups[0][0], "abc".parse().unwrap());
+
+        let groups = parse_groups("\r\nabc \r\n\r\n\r\na\r\nb\r\n\r\n");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0][0].len(), 3);
+        assert_eq!(groups[1].len(), 2);
     }
 
     #[test]
@@ -95,7 +120,7 @@ mod tests {
 
         let groups = parse_groups(TEST_INPUT);
         let group = &groups[2];
-        let unique_questions = unique_questions(&group);
+        let unique_questions = unique_questions(group);
         assert_eq!(unique_questions, 3);
     }
 
@@ -113,5 +138,28 @@ mod tests {
 
         let all_yes = all_answered(&groups[2]);
         assert_eq!(all_yes, 1);
+
+        assert_eq!(all_answered(&[]), 0);
+    }
+
+    #[test]
+    fn test_aggregation_at_least() {
+        advent_of_code::init();
+
+        let groups = parse_groups("ab\nac\nad\nbc");
+        let group = &groups[0];
+
+        assert_eq!(
+            Aggregation::AtLeast(1).apply(group),
+            Aggregation::Any.apply(group)
+        );
+        assert_eq!(Aggregation::AtLeast(2).apply(group).to_string(), "abc");
+        assert_eq!(Aggregation::AtLeast(3).apply(group).to_string(), "a");
+        assert_eq!(
+            Aggregation::AtLeast(4).apply(group),
+            Aggregation::All.apply(group)
+        );
+        assert!(Aggregation::AtLeast(5).apply(group).is_empty());
+        assert_eq!(Aggregation::AtLeast(0).apply(group), LetterSet::all());
     }
 }
@@ -1,106 +1,565 @@
 use std::collections::HashMap;
+use std::ops::Range;
 
-use lazy_static::lazy_static;
-use regex::Regex;
+use advent_of_code::graph::{ExportOptions, Graph, GraphError};
 
 static PROBLEM_NAME: &str = "2020-07";
 
-static DESIRED_BAG: &str = "shiny gold";
+/// The bag to look at when no color is given.
+static DEFAULT_BAG: &str = "shiny gold";
 
-lazy_static! {
-    static ref BAG_COLOR: Regex = Regex::new(r#"^(?P<color>\w+ \w+) bags?"#).unwrap();
-    static ref BAG_CHILDREN: Regex =
-        Regex::new(r#"(?P<count>\d+) (?P<color>\w+ \w+) bags?"#).unwrap();
+/// A text format the bag rules can be exported as.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ExportFormat {
+    Dot,
+    Mermaid,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(ExportFormat::Dot),
+            "mermaid" => Ok(ExportFormat::Mermaid),
+            _ => Err("unknown export format"),
+        }
+    }
+}
+
+/// A rule describing which bags must be inside of a bag.
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    /// The line the rule was on, starting from 1.
+    line: usize,
+    color: String,
+    /// Where the color is within the line.
+    span: Range<usize>,
+    contents: Vec<Content>,
+}
+
+/// A number of bags of a color required inside of another bag.
+#[derive(Clone, Debug, PartialEq)]
+struct Content {
+    count: usize,
+    color: String,
+    /// Where the color is within the line.
+    span: Range<usize>,
+}
+
+/// A problem with the bag rules, along with where it happened.
+#[derive(Clone, Debug, PartialEq)]
+struct ParseError {
+    /// The line with the problem, starting from 1.
+    line: usize,
+    /// Where the problem is within the line, in bytes.
+    span: Range<usize>,
+    /// The character where the problem starts within the line, starting
+    /// from 1.
+    column: usize,
+    message: String,
+}
+
+impl ParseError {
+    fn new(text: &str, line: usize, span: Range<usize>, message: String) -> Self {
+        Self {
+            line,
+            column: text[..span.start].chars().count() + 1,
+            span,
+            message,
+        }
+    }
+
+    /// Show the line with the problem, with the problem underlined.
+    fn snippet(&self, input: &str) -> String {
+        let text = input.lines().nth(self.line - 1).unwrap_or_default();
+        let start = text[..self.span.start].chars().count();
+        let len = text[self.span.clone()].chars().count().max(1);
+
+        format!("{}\n{}{}", text, " ".repeat(start), "^".repeat(len))
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// A piece of a rule line.
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind<'a> {
+    Word(&'a str),
+    Number(&'a str),
+    Comma,
+    Period,
+}
+
+impl std::fmt::Display for TokenKind<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Word(word) => write!(f, "\"{}\"", word),
+            TokenKind::Number(number) => write!(f, "number {}", number),
+            TokenKind::Comma => write!(f, "\",\""),
+            TokenKind::Period => write!(f, "\".\""),
+        }
+    }
+}
+
+/// A token and where it is within the line.
+#[derive(Clone, Debug, PartialEq)]
+struct Token<'a> {
+    kind: TokenKind<'a>,
+    span: Range<usize>,
+}
+
+/// Split a line into words, numbers, and punctuation.
+fn tokenize(line: usize, text: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        // Find the end of a run of characters matching a condition.
+        let mut end_of = |matches: fn(&char) -> bool| {
+            let mut end = start + c.len_utf8();
+            while let Some((index, c)) = chars.next_if(|(_, c)| matches(c)) {
+                end = index + c.len_utf8();
+            }
+            end
+        };
+
+        let kind = match c {
+            ' ' => continue,
+            ',' => TokenKind::Comma,
+            '.' => TokenKind::Period,
+            c if c.is_alphabetic() => {
+                let end = end_of(|c| c.is_alphabetic());
+                tokens.push(Token {
+                    kind: TokenKind::Word(&text[start..end]),
+                    span: start..end,
+                });
+                continue;
+            }
+            c if c.is_ascii_digit() => {
+                let end = end_of(|c| c.is_ascii_digit());
+                tokens.push(Token {
+                    kind: TokenKind::Number(&text[start..end]),
+                    span: start..end,
+                });
+                continue;
+            }
+            c => {
+                return Err(ParseError::new(
+                    text,
+                    line,
+                    start..start + c.len_utf8(),
+                    format!("unexpected character {:?}", c),
+                ))
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            span: start..start + c.len_utf8(),
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// Parses a single rule from its tokens, following the grammar:
+///
+/// ```text
+/// rule     = color "bags" "contain" contents "."
+/// contents = "no" "other" "bags" | content { "," content }
+/// content  = number color ( "bag" | "bags" )
+/// color    = word { word }
+/// ```
+struct RuleParser<'a> {
+    line: usize,
+    /// The text of the line, for reporting where problems are.
+    text: &'a str,
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> RuleParser<'a> {
+    /// Look at the next token without consuming it.
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Create an error for the next token, or the end of the line.
+    fn error(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError::new(
+                self.text,
+                self.line,
+                token.span.clone(),
+                format!("expected {}, found {}", expected, token.kind),
+            ),
+            None => ParseError::new(
+                self.text,
+                self.line,
+                self.text.len()..self.text.len(),
+                format!("expected {}, found end of line", expected),
+            ),
+        }
+    }
+
+    /// Consume the next token if it's an exact word.
+    fn word(&mut self, word: &str) -> Result<Range<usize>, ParseError> {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Word(found),
+                span,
+            }) if *found == word => {
+                let span = span.clone();
+                self.pos += 1;
+                Ok(span)
+            }
+            _ => Err(self.error(&format!("\"{}\"", word))),
+        }
+    }
+
+    /// Consume words until reaching a word that ends a color.
+    fn color(&mut self) -> Result<(String, Range<usize>), ParseError> {
+        let mut words = Vec::new();
+        let mut span: Option<Range<usize>> = None;
+
+        while let Some(Token {
+            kind: TokenKind::Word(word),
+            span: word_span,
+        }) = self.peek()
+        {
+            if ["bag", "bags", "contain"].contains(word) {
+                break;
+            }
+
+            words.push(*word);
+            span = Some(match span {
+                Some(span) => span.start..word_span.end,
+                None => word_span.clone(),
+            });
+            self.pos += 1;
+        }
+
+        match span {
+            Some(span) => Ok((words.join(" "), span)),
+            None => Err(self.error("a color")),
+        }
+    }
+
+    /// Consume a bag count and its color.
+    fn content(&mut self) -> Result<Content, ParseError> {
+        let (count, count_span) = match self.peek() {
+            Some(Token {
+                kind: TokenKind::Number(number),
+                span,
+            }) => (number.parse::<usize>().ok(), span.clone()),
+            _ => return Err(self.error("a number of bags")),
+        };
+        self.pos += 1;
+
+        let count = match count {
+            Some(count) if count > 0 => count,
+            _ => {
+                return Err(ParseError::new(
+                    self.text,
+                    self.line,
+                    count_span,
+                    "number of bags must be a positive number".to_string(),
+                ))
+            }
+        };
+
+        let (color, span) = self.color()?;
+
+        // A single bag must be singular and anything else must be plural.
+        if count == 1 {
+            self.word("bag")?;
+        } else {
+            self.word("bags")?;
+        }
+
+        Ok(Content { count, color, span })
+    }
+
+    /// Consume an entire rule.
+    fn rule(&mut self) -> Result<Rule, ParseError> {
+        let (color, span) = self.color()?;
+        self.word("bags")?;
+        self.word("contain")?;
+
+        let mut contents = Vec::new();
+
+        if self.word("no").is_ok() {
+            self.word("other")?;
+            self.word("bags")?;
+        } else {
+            loop {
+                contents.push(self.content()?);
+
+                match self.peek().map(|token| &token.kind) {
+                    Some(TokenKind::Comma) => self.pos += 1,
+                    _ => break,
+                }
+            }
+        }
+
+        match self.peek().map(|token| &token.kind) {
+            Some(TokenKind::Period) => self.pos += 1,
+            _ if contents.is_empty() => return Err(self.error("\".\"")),
+            _ => return Err(self.error("\",\" or \".\"")),
+        }
+
+        if self.peek().is_some() {
+            return Err(self.error("end of line"));
+        }
+
+        Ok(Rule {
+            line: self.line,
+            color,
+            span,
+            contents,
+        })
+    }
+}
+
+/// Parse a single rule from a line.
+fn parse_rule(line: usize, text: &str) -> Result<Rule, ParseError> {
+    let mut parser = RuleParser {
+        line,
+        text,
+        tokens: tokenize(line, text)?,
+        pos: 0,
+    };
+
+    parser.rule()
+}
+
+/// Parse every rule, making sure each color is only defined once and every
+/// color inside a bag has its own rule. All problems found are returned.
+fn parse_rules(input: &str) -> Result<Vec<Rule>, Vec<ParseError>> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut rules = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, text) in lines.iter().enumerate() {
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        match parse_rule(index + 1, text) {
+            Ok(rule) => rules.push(rule),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    // References can't be checked if a line failed to parse, as the missing
+    // rule may have been the definition.
+    let check_references = errors.is_empty();
+
+    // Remember where each color was first defined.
+    let mut defined: HashMap<&str, usize> = HashMap::new();
+    for rule in &rules {
+        if let Some(line) = defined.get(rule.color.as_str()) {
+            errors.push(ParseError::new(
+                lines[rule.line - 1],
+                rule.line,
+                rule.span.clone(),
+                format!("{} bags were already defined on line {}", rule.color, line),
+            ));
+        } else {
+            defined.insert(&rule.color, rule.line);
+        }
+    }
+
+    if check_references {
+        for rule in &rules {
+            for content in &rule.contents {
+                if !defined.contains_key(content.color.as_str()) {
+                    errors.push(ParseError::new(
+                        lines[rule.line - 1],
+                        rule.line,
+                        content.span.clone(),
+                        format!("{} bags are never defined", content.color),
+                    ));
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(rules)
+    } else {
+        errors.sort_by_key(|err| (err.line, err.span.start));
+        Err(errors)
+    }
 }
 
 fn main() {
     advent_of_code::init();
 
+    // The bag color can be provided as the first argument.
+    let color: String = advent_of_code::utils::arg(0).unwrap_or_else(|| DEFAULT_BAG.to_string());
+
     let input = advent_of_code::load_input(PROBLEM_NAME);
-    let bags = parse_bags(&input);
+    let bags = match parse_rules(&input) {
+        Ok(rules) => rules_to_bags(&rules),
+        Err(errors) => {
+            for err in &errors {
+                log::error!("{}\n{}", err, err.snippet(&input));
+            }
+            log::error!("Found {} problems with bag rules", errors.len());
+            std::process::exit(1);
+        }
+    };
 
-    let can_contain = bags
-        .keys()
-        .filter(|key| *key != DESIRED_BAG)
-        .filter(|color| bag_can_contain(&bags, color))
-        .count();
-    log::info!("Part 1 = {}", can_contain);
+    // Instead of solving, the rules can be exported by providing a format as
+    // the second argument. If the third argument is true, only the bags
+    // inside the chosen bag are included.
+    if let Some(format) = advent_of_code::utils::arg(1) {
+        let prune = advent_of_code::utils::arg(2).unwrap_or(false);
+        match export_bags(&bags, &color, format, prune) {
+            Ok(export) => print!("{}", export),
+            Err(err) => {
+                log::error!("Unable to export bags: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let bags = build_graph(&bags);
+
+    if let Err(err) = bags.check_acyclic() {
+        log::error!("Bag rules are invalid: {}", err);
+        std::process::exit(1);
+    }
 
-    let child_bags = child_bags(&bags, DESIRED_BAG);
+    let can_contain = match bag_can_contain(&bags, &color) {
+        Ok(can_contain) => can_contain,
+        Err(err) => {
+            log::error!("Unable to find bags that can contain {}: {}", color, err);
+            std::process::exit(1);
+        }
+    };
+    log::info!("Part 1 = {}", can_contain.len());
+
+    let child_bags = match child_bags(&bags, &color) {
+        Ok(child_bags) => child_bags,
+        Err(err) => {
+            log::error!("Unable to count bags inside {}: {}", color, err);
+            std::process::exit(1);
+        }
+    };
     log::info!("Part 2 = {}", child_bags);
 }
 
+/// Convert rules into a hash map with information about what and how many bags
+/// they can contain.
+fn rules_to_bags(rules: &[Rule]) -> HashMap<String, Vec<(String, usize)>> {
+    rules
+        .iter()
+        .map(|rule| {
+            let contents = rule
+                .contents
+                .iter()
+                .map(|content| (content.color.clone(), content.count))
+                .collect();
+
+            (rule.color.clone(), contents)
+        })
+        .collect()
+}
+
 /// Parse all bags into a hash map with information about what and how many
 /// bags they can contain.
+///
+/// # Panics
+///
+/// Will panic if the rules are not valid.
+#[cfg(test)]
 fn parse_bags(input: &str) -> HashMap<String, Vec<(String, usize)>> {
-    let mut bags = HashMap::new();
+    match parse_rules(input) {
+        Ok(rules) => rules_to_bags(&rules),
+        Err(errors) => panic!("bag rules were invalid: {}", errors[0]),
+    }
+}
 
-    for line in input.lines() {
-        let color = BAG_COLOR.captures(&line).unwrap()["color"].to_string();
-        let can_contain: Vec<_> = BAG_CHILDREN
-            .captures_iter(&line)
-            .map(|capture| {
-                let count: usize = capture["count"].parse().unwrap();
-                let color = capture["color"].to_string();
+/// Build a graph where each bag has an edge to the bags it directly contains,
+/// weighted by how many of them it holds.
+fn build_graph(bags: &HashMap<String, Vec<(String, usize)>>) -> Graph {
+    let mut graph = Graph::new();
 
-                (color, count)
-            })
-            .collect();
+    for (color, children) in bags {
+        let parent = graph.intern(color);
 
-        bags.insert(color, can_contain);
+        for (child_color, count) in children {
+            let child = graph.intern(child_color);
+            graph.add_edge(parent, child, *count);
+        }
     }
 
-    bags
+    graph
 }
 
-/// Determine if a bag is capable of holding another bag, either directly or
-/// through its children.
-fn bag_can_contain(bags: &HashMap<String, Vec<(String, usize)>>, color: &str) -> bool {
-    // If we're at the desired bag, we can hold it!
-    if color == DESIRED_BAG {
-        return true;
-    }
+/// Export bags as text in a format suitable for visualizing, highlighting the
+/// bags that contain or are contained by a chosen bag. The export can be
+/// pruned to only the bags inside the chosen bag.
+fn export_bags(
+    bags: &HashMap<String, Vec<(String, usize)>>,
+    color: &str,
+    format: ExportFormat,
+    prune: bool,
+) -> Result<String, GraphError> {
+    let graph = build_graph(bags);
+    let id = graph.try_id(color)?;
 
-    // Get our children to see if any of them can hold it.
-    let children = match bags.get(color) {
-        Some(children) => children,
-        None => return false,
+    let options = ExportOptions {
+        highlight: Some(id),
+        root: if prune { Some(id) } else { None },
     };
 
-    // Check children recursively to see if they are it or if they can hold it.
-    for (child_color, _count) in children {
-        if child_color == DESIRED_BAG {
-            return true;
-        }
+    Ok(match format {
+        ExportFormat::Dot => graph.to_dot(&options),
+        ExportFormat::Mermaid => graph.to_mermaid(&options),
+    })
+}
 
-        if bag_can_contain(&bags, child_color) {
-            return true;
-        }
-    }
+/// Find the colors of every bag capable of holding another bag, either
+/// directly or through its children.
+fn bag_can_contain<'a>(bags: &'a Graph, color: &str) -> Result<Vec<&'a str>, GraphError> {
+    let id = bags.try_id(color)?;
 
-    false
+    // Every bag that leads to this bag is able to contain it.
+    let mut colors: Vec<_> = bags
+        .ancestors(id)
+        .iter()
+        .map(|ancestor| bags.name(*ancestor))
+        .collect();
+    colors.sort_unstable();
+
+    Ok(colors)
 }
 
 /// Calculate the maximum number of child bags that can be held by a parent bag.
-fn child_bags(bags: &HashMap<String, Vec<(String, usize)>>, color: &str) -> usize {
-    let children = match bags.get(color) {
-        Some(children) => children,
-        None => return 0,
-    };
-
+fn child_bags(bags: &Graph, color: &str) -> Result<usize, GraphError> {
     // Each bag contains a specific number of direct children and all of the
     // children within those bags.
-    children.iter().fold(0, |acc, (color, count)| {
-        acc + count + count * child_bags(&bags, &color)
-    })
+    bags.total_weight(bags.try_id(color)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    static TEST_INPUT: &'static str =
-        "light red bags contain 1 bright white bag, 2 muted yellow bags.
+    static TEST_INPUT: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
 dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 bright white bags contain 1 shiny gold bag.
 muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
@@ -110,7 +569,7 @@ vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 faded blue bags contain no other bags.
 dotted black bags contain no other bags.";
 
-    static OTHER_TEST_INPUT: &'static str = "shiny gold bags contain 2 dark red bags.
+    static OTHER_TEST_INPUT: &str = "shiny gold bags contain 2 dark red bags.
 dark red bags contain 2 dark orange bags.
 dark orange bags contain 2 dark yellow bags.
 dark yellow bags contain 2 dark green bags.
@@ -129,21 +588,207 @@ dark violet bags contain no other bags.";
         assert_eq!(bags["light red"][1].1, 2);
     }
 
+    #[test]
+    fn test_parse_rule() {
+        advent_of_code::init();
+
+        let rule = parse_rule(
+            1,
+            "light red bags contain 1 bright white bag, 2 muted yellow bags.",
+        );
+        assert_eq!(
+            rule,
+            Ok(Rule {
+                line: 1,
+                color: "light red".to_string(),
+                span: 0..9,
+                contents: vec![
+                    Content {
+                        count: 1,
+                        color: "bright white".to_string(),
+                        span: 25..37,
+                    },
+                    Content {
+                        count: 2,
+                        color: "muted yellow".to_string(),
+                        span: 45..57,
+                    },
+                ],
+            })
+        );
+
+        let rule = parse_rule(1, "faded blue bags contain no other bags.").unwrap();
+        assert!(rule.contents.is_empty());
+    }
+
+    #[test]
+    fn test_parse_rule_multi_word_colors() {
+        advent_of_code::init();
+
+        let rule = parse_rule(1, "pale shiny gold bags contain 3 red bags.").unwrap();
+        assert_eq!(rule.color, "pale shiny gold");
+        assert_eq!(rule.contents[0].color, "red");
+    }
+
+    #[test]
+    fn test_parse_rule_errors() {
+        advent_of_code::init();
+
+        let cases = &[
+            (
+                "light red bags 1 bright white bag.",
+                15..16,
+                "expected \"contain\", found number 1",
+            ),
+            (
+                "light red bags contain no othr bags.",
+                26..30,
+                "expected \"other\", found \"othr\"",
+            ),
+            (
+                "light red bags contain no other bag.",
+                32..35,
+                "expected \"bags\", found \"bag\"",
+            ),
+            (
+                "light red bags contain 2 white bag.",
+                31..34,
+                "expected \"bags\", found \"bag\"",
+            ),
+            (
+                "light red bags contain 0 white bags.",
+                23..24,
+                "number of bags must be a positive number",
+            ),
+            (
+                "light red bags contain 1 white bag",
+                34..34,
+                "expected \",\" or \".\", found end of line",
+            ),
+            (
+                "bags contain no other bags.",
+                0..4,
+                "expected a color, found \"bags\"",
+            ),
+            (
+                "light red bags contain 1 white bag; 2 blue bags.",
+                34..35,
+                "unexpected character ';'",
+            ),
+        ];
+
+        for (input, span, message) in cases {
+            let err = parse_rule(3, input).unwrap_err();
+            assert_eq!(err.line, 3, "{}", input);
+            assert_eq!(&err.span, span, "{}", input);
+            assert_eq!(&err.message, message, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_rules_errors() {
+        advent_of_code::init();
+
+        let input = "light red bags contain 2 white bags.
+light red bags contain no other bags.
+white bags contain 1 blue bag.";
+        let errors = parse_rules(input).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "line 2, column 1: light red bags were already defined on line 1"
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "line 3, column 22: blue bags are never defined"
+        );
+        assert_eq!(
+            errors[1].snippet(input),
+            "white bags contain 1 blue bag.\n                     ^^^^"
+        );
+
+        // Columns count characters rather than bytes.
+        let input = "crème bags contain 1 blue bag.";
+        let errors = parse_rules(input).unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "line 1, column 22: blue bags are never defined"
+        );
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        advent_of_code::init();
+
+        let rules = parse_rules(TEST_INPUT).unwrap();
+        assert_eq!(rules.len(), 9);
+        assert_eq!(rules[8].line, 9);
+        assert_eq!(rules[8].color, "dotted black");
+    }
+
     #[test]
     fn test_bag_can_contain() {
         advent_of_code::init();
 
-        let bags = parse_bags(TEST_INPUT);
-        assert_eq!(bag_can_contain(&bags, "shiny gold"), true);
-        assert_eq!(bag_can_contain(&bags, "bright white"), true);
-        assert_eq!(bag_can_contain(&bags, "faded blue"), false);
+        let bags = build_graph(&parse_bags(TEST_INPUT));
+        assert_eq!(
+            bag_can_contain(&bags, "shiny gold"),
+            Ok(vec![
+                "bright white",
+                "dark orange",
+                "light red",
+                "muted yellow"
+            ])
+        );
+        assert_eq!(
+            bag_can_contain(&bags, "faded blue").map(|colors| colors.len()),
+            Ok(7)
+        );
+        assert_eq!(bag_can_contain(&bags, "light red"), Ok(vec![]));
+        assert_eq!(
+            bag_can_contain(&bags, "plaid red"),
+            Err(GraphError::UnknownNode("plaid red".to_string()))
+        );
     }
 
     #[test]
     fn test_child_bags() {
         advent_of_code::init();
 
+        let bags = build_graph(&parse_bags(TEST_INPUT));
+        assert_eq!(child_bags(&bags, DEFAULT_BAG), Ok(32));
+
+        let bags = build_graph(&parse_bags(OTHER_TEST_INPUT));
+        assert_eq!(child_bags(&bags, DEFAULT_BAG), Ok(126));
+        assert_eq!(child_bags(&bags, "dark blue"), Ok(2));
+    }
+
+    #[test]
+    fn test_cycle() {
+        advent_of_code::init();
+
+        let input = "shiny gold bags contain 2 dark red bags.
+dark red bags contain 1 shiny gold bag.";
+        let bags = build_graph(&parse_bags(input));
+        assert!(bags.check_acyclic().is_err());
+        assert!(child_bags(&bags, DEFAULT_BAG).is_err());
+    }
+
+    #[test]
+    fn test_export_bags() {
+        advent_of_code::init();
+
         let bags = parse_bags(OTHER_TEST_INPUT);
-        assert_eq!(child_bags(&bags, DESIRED_BAG), 126);
+
+        let dot = export_bags(&bags, "dark blue", ExportFormat::Dot, true).unwrap();
+        assert_eq!(dot.lines().count(), 5);
+        assert!(dot.contains(r##"[label="dark blue", style=filled, fillcolor="#ffd700"]"##));
+        assert!(dot.contains(r#"[label="2"]"#));
+
+        let mermaid = export_bags(&bags, "dark blue", ExportFormat::Mermaid, false).unwrap();
+        assert!(mermaid.starts_with("graph TD\n"));
+        assert_eq!(mermaid.matches("-->|2|").count(), 6);
+
+        assert!(export_bags(&bags, "plaid red", ExportFormat::Dot, false).is_err());
     }
 }
@@ -182,7 +182,7 @@ mod test {
         advent_of_code::init();
 
         let input = "nop 0\njmp -1";
-        let lines: Vec<Line> = advent_of_code::utils::decode_line(&input);
+        let lines: Vec<Line> = advent_of_code::utils::decode_line(input);
 
         let acc = run_until_duplicate(&lines);
         assert_eq!(acc, 0);
@@ -193,10 +193,10 @@ mod test {
         advent_of_code::init();
 
         let input = "nop 0";
-        let lines: Vec<Line> = advent_of_code::utils::decode_line(&input);
+        let lines: Vec<Line> = advent_of_code::utils::decode_line(input);
 
         let (completed, acc) = will_complete(&lines);
-        assert_eq!(completed, true);
+        assert!(completed);
         assert_eq!(acc, 0);
     }
 
@@ -205,7 +205,7 @@ mod test {
         advent_of_code::init();
 
         let input = "nop 0\njmp -1";
-        let lines: Vec<Line> = advent_of_code::utils::decode_line(&input);
+        let lines: Vec<Line> = advent_of_code::utils::decode_line(input);
 
         let acc = flip_until_complete(&lines);
         assert_eq!(acc, 0);
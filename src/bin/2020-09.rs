@@ -1,105 +1,85 @@
-use std::collections::VecDeque;
+use std::io::BufRead;
+
+use advent_of_code::sums::{find_contiguous_sum, invalid_sums};
 
 static PROBLEM_NAME: &str = "2020-09";
 
+/// Number of previous values to check against when no preamble size is given.
+const DEFAULT_PREAMBLE: usize = 25;
+
 fn main() {
     advent_of_code::init();
 
-    let input = advent_of_code::load_input(PROBLEM_NAME);
-    let numbers: Vec<i64> = advent_of_code::utils::decode_line(&input);
+    // The preamble size can be provided as the first argument.
+    let preamble = advent_of_code::utils::arg(0).unwrap_or(DEFAULT_PREAMBLE);
+    log::debug!("Using preamble of {} values", preamble);
+
+    let invalid: Vec<_> =
+        find_invalid(advent_of_code::open_input(PROBLEM_NAME), preamble).collect();
+    for (index, number) in &invalid {
+        log::info!("Number {} at index {} is invalid", number, index);
+    }
+    log::debug!("Found {} invalid numbers", invalid.len());
 
-    let first_invalid = find_first_invalid(&numbers, 25);
+    let (_index, first_invalid) = *invalid.first().expect("unable to find invalid entry");
     log::info!("Part 1 = {}", first_invalid);
 
+    let input = advent_of_code::load_input(PROBLEM_NAME);
+    let numbers: Vec<i64> = advent_of_code::utils::decode_line(&input);
+
     let numbers = find_any_that_sum(&numbers, first_invalid);
-    let sum = sum_min_max(&numbers);
+    let sum = sum_min_max(numbers);
     log::info!("Part 2 = {}", sum);
 }
 
+/// Find every number in a stream of lines which cannot be created by summing
+/// any two of the previous n values, along with its index. Lines are only read
+/// as they are needed.
+///
+/// # Panics
+///
+/// Will panic if a line is not a number.
+fn find_invalid<R: BufRead>(
+    reader: R,
+    previous_count: usize,
+) -> impl Iterator<Item = (usize, i64)> {
+    let numbers = advent_of_code::utils::decode_reader(reader);
+
+    invalid_sums(numbers, previous_count)
+}
+
 /// Find the first number which cannot be created by summing any two of the
 /// previous n values.
 ///
 /// # Panics
 ///
 /// Will panic if there are no numbers that solve it.
+#[cfg(test)]
 fn find_first_invalid(lines: &[i64], previous_count: usize) -> i64 {
-    // Allocate a buffer for our n previous values, plus an extra for when a
-    // new value has been added.
-    let mut previous = VecDeque::with_capacity(previous_count + 1);
-
-    for line in lines {
-        // Make sure only the correct number of values are being evaluated.
-        while previous.len() > previous_count {
-            log::trace!(
-                "Removing old previous value, length is {} while desired is {}",
-                previous.len(),
-                previous_count
-            );
-
-            previous.pop_front();
-        }
-
-        // Only start evaluating once we have processed n previous values.
-        if previous.len() >= previous_count {
-            log::trace!("Evaluating {} with previous values {:?}", line, previous);
-            let mut has_sum = false;
-
-            // Iterate through each combination, checking if any value sums to
-            // the line. Once we've found a single combination, we can break
-            // early.
-            'outer: for (i1, p1) in previous.iter().enumerate() {
-                for (i2, p2) in previous.iter().enumerate() {
-                    if i1 == i2 {
-                        continue;
-                    }
-
-                    if p1 + p2 == *line {
-                        log::trace!("Combination {} + {} = {}", p1, p2, line);
-                        has_sum = true;
-                        break 'outer;
-                    }
-                }
-            }
-
-            // If we never found the numbers, we've found our solution.
-            if !has_sum {
-                log::debug!("{} has no previous combinations", line);
-                return *line;
-            }
-        }
-
-        previous.push_back(*line);
-    }
+    let (_index, number) = invalid_sums(lines.iter().copied(), previous_count)
+        .next()
+        .expect("unable to find invalid entry");
 
-    panic!("unable to find invalid entry");
+    number
 }
 
-/// Find two or more numbers that sum to a total.
+/// Find two or more contiguous numbers that sum to a total.
+///
+/// If several ranges work, returns the one that ends earliest, preferring the
+/// longest one if several end at the same position.
 ///
 /// # Panics
 ///
-/// This will probably panic if there are no working combinations.
+/// Will panic if there are no working combinations.
 fn find_any_that_sum(lines: &[i64], sum_to: i64) -> &[i64] {
-    // Start by looking at at least 2 numbers
-    let mut nums = 2;
-
-    loop {
-        log::debug!("Checking combinations with {} numbers", nums);
-
-        // Iterate through each possible number, minus the number we're trying.
-        // This strategy is one way of making sure we don't exceed the length
-        // when trying larger number combinations. If any of these combinations
-        // sum to our desired value, we're done.
-        for pos in nums..lines.len() {
-            let check = &lines[pos - nums..pos];
-            if check.iter().sum::<i64>() == sum_to {
-                log::debug!("Found combination {:?} that sums to {}", check, sum_to);
-                return check;
-            }
-        }
+    let range = find_contiguous_sum(lines, sum_to, 2).expect("no numbers sum to total");
+    log::debug!(
+        "Found combination {:?} that sums to {}",
+        &lines[range.clone()],
+        sum_to
+    );
 
-        nums += 1;
-    }
+    &lines[range]
 }
 
 /// Sum the min and max numbers.
@@ -113,8 +93,95 @@ fn sum_min_max(numbers: &[i64]) -> i64 {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+
     use super::*;
 
+    // The original brute force implementations, kept as references for the
+    // faster versions.
+
+    /// Find the first number which cannot be created by summing any two of the
+    /// previous n values.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are no numbers that solve it.
+    fn brute_force_first_invalid(lines: &[i64], previous_count: usize) -> i64 {
+        // Allocate a buffer for our n previous values, plus an extra for when a
+        // new value has been added.
+        let mut previous = VecDeque::with_capacity(previous_count + 1);
+
+        for line in lines {
+            // Make sure only the correct number of values are being evaluated.
+            while previous.len() > previous_count {
+                log::trace!(
+                    "Removing old previous value, length is {} while desired is {}",
+                    previous.len(),
+                    previous_count
+                );
+
+                previous.pop_front();
+            }
+
+            // Only start evaluating once we have processed n previous values.
+            if previous.len() >= previous_count {
+                log::trace!("Evaluating {} with previous values {:?}", line, previous);
+                let mut has_sum = false;
+
+                // Iterate through each combination, checking if any value sums to
+                // the line. Once we've found a single combination, we can break
+                // early.
+                'outer: for (i1, p1) in previous.iter().enumerate() {
+                    for (i2, p2) in previous.iter().enumerate() {
+                        if i1 == i2 {
+                            continue;
+                        }
+
+                        if p1 + p2 == *line {
+                            log::trace!("Combination {} + {} = {}", p1, p2, line);
+                            has_sum = true;
+                            break 'outer;
+                        }
+                    }
+                }
+
+                // If we never found the numbers, we've found our solution.
+                if !has_sum {
+                    log::debug!("{} has no previous combinations", line);
+                    return *line;
+                }
+            }
+
+            previous.push_back(*line);
+        }
+
+        panic!("unable to find invalid entry");
+    }
+
+    /// Find two or more contiguous numbers that sum to a total, by checking
+    /// every range. Used to check [`find_any_that_sum`], so it returns the
+    /// range that ends earliest, preferring the longest one if several end at
+    /// the same position.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are no working combinations.
+    fn brute_force_any_that_sum(lines: &[i64], sum_to: i64) -> &[i64] {
+        // Look at each possible end in order, and for each one try the longest
+        // range first by starting as early as possible.
+        for end in 2..=lines.len() {
+            for start in 0..=end - 2 {
+                let check = &lines[start..end];
+                if check.iter().sum::<i64>() == sum_to {
+                    log::debug!("Found combination {:?} that sums to {}", check, sum_to);
+                    return check;
+                }
+            }
+        }
+
+        panic!("no numbers sum to total");
+    }
+
     static NUMBERS: &[i64] = &[
         35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576,
     ];
@@ -127,6 +194,24 @@ mod tests {
         assert_eq!(first_invalid, 127);
     }
 
+    #[test]
+    fn test_find_invalid() {
+        advent_of_code::init();
+
+        let input = NUMBERS
+            .iter()
+            .map(|number| number.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let invalid: Vec<_> = find_invalid(input.as_bytes(), 5).collect();
+        assert_eq!(invalid, vec![(14, 127)]);
+
+        let invalid: Vec<_> = find_invalid(input.as_bytes(), 2).collect();
+        assert_eq!(invalid[0], (2, 15));
+        assert_eq!(invalid.len(), 18);
+    }
+
     #[test]
     fn test_find_any_that_sum() {
         advent_of_code::init();
@@ -140,4 +225,65 @@ mod tests {
         let numbers = &[1, 2, 3];
         assert_eq!(sum_min_max(numbers), 4);
     }
+
+    /// A small deterministic generator so comparisons are reproducible.
+    fn pseudo_random(seed: &mut u64, range: i64) -> i64 {
+        *seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((*seed >> 33) as i64 % (2 * range + 1)) - range
+    }
+
+    #[test]
+    fn test_find_first_invalid_matches_brute_force() {
+        advent_of_code::init();
+
+        let mut seed = 2020;
+        for preamble in 1..8 {
+            let mut numbers: Vec<i64> = (0..200).map(|_| pseudo_random(&mut seed, 20)).collect();
+            // Make sure there's always a value that can't be made from a pair.
+            numbers.push(i64::MAX / 2);
+
+            assert_eq!(
+                find_first_invalid(&numbers, preamble),
+                brute_force_first_invalid(&numbers, preamble),
+                "preamble {} with numbers {:?}",
+                preamble,
+                numbers
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_any_that_sum_finds_valid_range() {
+        advent_of_code::init();
+
+        let mut seed = 2020;
+        for _ in 0..50 {
+            let numbers: Vec<i64> = (0..100).map(|_| pseudo_random(&mut seed, 50)).collect();
+
+            // Pick a target that we know can be made by some range.
+            let start = (pseudo_random(&mut seed, 40) + 40) as usize;
+            let len = (pseudo_random(&mut seed, 8) + 10) as usize;
+            let target: i64 = numbers[start..start + len].iter().sum();
+
+            let found = find_any_that_sum(&numbers, target);
+            let expected = brute_force_any_that_sum(&numbers, target);
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_generated_input() {
+        advent_of_code::init();
+
+        for input in advent_of_code::generate::inputs(PROBLEM_NAME, 3, 300) {
+            let numbers: Vec<i64> = advent_of_code::utils::decode_line(&input);
+
+            assert_eq!(
+                find_first_invalid(&numbers, DEFAULT_PREAMBLE),
+                brute_force_first_invalid(&numbers, DEFAULT_PREAMBLE)
+            );
+        }
+    }
 }
@@ -1,28 +1,337 @@
 use std::collections::HashMap;
 
+use advent_of_code::answer::Answer;
+use advent_of_code::dag;
+
 static PROBLEM_NAME: &str = "2020-10";
 
+/// The differences allowed between adapters in a chain.
+const DEFAULT_STEPS: &[usize] = &[1, 2, 3];
+
+/// How much higher the device is rated than the highest adapter.
+const DEFAULT_DEVICE_OFFSET: usize = 3;
+
+/// Number of example arrangements to show when debugging.
+const SHOWN_ARRANGEMENTS: usize = 5;
+
+/// A problem that prevents a chain of adapters from being completed.
+#[derive(Clone, Debug, PartialEq)]
+enum ChainError {
+    /// The next adapter is rated too far above the previous one.
+    Gap { from: usize, to: usize },
+    /// The difference between adapters is within range but not allowed.
+    InvalidStep { from: usize, to: usize },
+    /// No arrangement of adapters gets from the outlet to the device.
+    Unreachable { reached: usize, device: usize },
+}
+
+impl std::fmt::Display for ChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainError::Gap { from, to } => {
+                write!(f, "nothing can connect {} jolts to {} jolts", from, to)
+            }
+            ChainError::InvalidStep { from, to } => write!(
+                f,
+                "difference of {} between {} jolts and {} jolts is not allowed",
+                to - from,
+                from,
+                to
+            ),
+            ChainError::Unreachable { reached, device } => write!(
+                f,
+                "adapters only reach {} jolts, which cannot connect to the device at {} jolts",
+                reached, device
+            ),
+        }
+    }
+}
+
+/// The differences allowed between adapters, parsed from a comma separated
+/// list such as `1,3`.
+#[derive(Clone, Debug, PartialEq)]
+struct Steps(Vec<usize>);
+
+impl std::str::FromStr for Steps {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = s
+            .split(',')
+            .map(|step| step.trim().parse())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| "steps must be numbers separated by commas")?;
+
+        if steps.contains(&0) {
+            return Err("steps must be greater than zero");
+        }
+
+        steps.sort_unstable();
+        steps.dedup();
+
+        Ok(Self(steps))
+    }
+}
+
+impl std::fmt::Display for Steps {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let steps: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+        write!(f, "{}", steps.join(","))
+    }
+}
+
 fn main() {
     advent_of_code::init();
 
+    // The device offset and allowed steps can be provided as the first and
+    // second arguments.
+    let device_offset = advent_of_code::utils::arg(0).unwrap_or(DEFAULT_DEVICE_OFFSET);
+    let steps = advent_of_code::utils::arg(1).unwrap_or_else(|| Steps(DEFAULT_STEPS.to_vec()));
+
+    // The device is always connected directly to the highest adapter, so the
+    // offset has to be a step the device accepts.
+    if !steps.0.contains(&device_offset) {
+        log::error!(
+            "Device offset of {} jolts is not one of the allowed steps {}",
+            device_offset,
+            steps
+        );
+        std::process::exit(1);
+    }
+
     let input = advent_of_code::load_input(PROBLEM_NAME);
     let mut lines: Vec<usize> = advent_of_code::utils::decode_line(&input);
-    fix_input(&mut lines);
+    fix_input(&mut lines, device_offset);
 
-    let steps = count_steps(&lines);
-    log::info!("Part 1 = {}", steps[&1] * steps[&3]);
+    if let Err(err) = check_complete(&lines, &steps.0) {
+        log::error!("Adapters cannot be chained: {}", err);
+        std::process::exit(1);
+    }
 
-    let mut visited = Default::default();
-    let paths = count_paths(&lines, 0, &mut visited);
+    // Part 1 needs a chain using every adapter, but arrangements can still be
+    // counted without one.
+    match check_chain(&lines, &steps.0) {
+        Ok(()) => {
+            let (mandatory, removable) = classify_adapters(&lines, &steps.0);
+            log::debug!("Mandatory adapters: {:?}", mandatory);
+            log::debug!("Removable adapters: {:?}", removable);
+
+            let counts = count_steps(&lines);
+            let count = |step| counts.get(&step).copied().unwrap_or_default();
+            log::info!("Part 1 = {}", Answer::product(vec![count(1), count(3)]));
+        }
+        Err(err) => log::warn!("Not every adapter can be used, skipping part 1: {}", err),
+    }
+
+    if log::log_enabled!(log::Level::Debug) {
+        for arrangement in arrangements(&lines, &steps.0).take(SHOWN_ARRANGEMENTS) {
+            log::debug!("Possible arrangement: {:?}", arrangement);
+        }
+    }
+
+    let paths = count_paths(&lines, &steps.0);
     log::info!("Part 2 = {}", paths);
 }
 
 /// Clean up input by sorting entries, inserting a zero value at the beginning,
-/// and a value 3 greater than the last value.
-fn fix_input(jolts: &mut Vec<usize>) {
+/// and a value for the device some offset greater than the last value.
+fn fix_input(jolts: &mut Vec<usize>, device_offset: usize) {
     jolts.sort();
     jolts.insert(0, 0);
-    jolts.push(jolts.last().unwrap() + 3);
+    jolts.push(jolts.last().unwrap() + device_offset);
+}
+
+/// Make sure every adapter can be used in a single chain, meaning each
+/// difference between values is an allowed step. Items must be sorted for
+/// this to work.
+fn check_chain(jolts: &[usize], steps: &[usize]) -> Result<(), ChainError> {
+    let max_step = steps.iter().copied().max().unwrap_or_default();
+
+    for pair in jolts.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let diff = to - from;
+
+        if diff > max_step {
+            return Err(ChainError::Gap { from, to });
+        }
+
+        if !steps.contains(&diff) {
+            return Err(ChainError::InvalidStep { from, to });
+        }
+    }
+
+    Ok(())
+}
+
+/// Make sure at least one arrangement of adapters gets from the first to the
+/// last value, even if some adapters can't be used. Items must be sorted for
+/// this to work.
+fn check_complete(jolts: &[usize], steps: &[usize]) -> Result<(), ChainError> {
+    let max_step = steps.iter().copied().max().unwrap_or_default();
+
+    // Walk forward marking every position that can be reached from the start.
+    let mut reachable = vec![false; jolts.len()];
+    reachable[0] = true;
+
+    for pos in 0..jolts.len() {
+        if !reachable[pos] {
+            continue;
+        }
+
+        for index in (pos + 1..jolts.len())
+            .take_while(|index| jolts[*index] - jolts[pos] <= max_step)
+            .filter(|index| steps.contains(&(jolts[*index] - jolts[pos])))
+        {
+            reachable[index] = true;
+        }
+    }
+
+    if reachable[jolts.len() - 1] {
+        return Ok(());
+    }
+
+    let reached = (0..jolts.len())
+        .rev()
+        .find(|pos| reachable[*pos])
+        .map(|pos| jolts[pos])
+        .unwrap_or_default();
+
+    Err(ChainError::Unreachable {
+        reached,
+        device: jolts[jolts.len() - 1],
+    })
+}
+
+/// Split adapters into those that must be in every arrangement and those that
+/// can be removed from at least one. The outlet and device are not included.
+/// Items must be sorted and pass [`check_chain`] for this to work.
+fn classify_adapters(jolts: &[usize], steps: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    let max_step = steps.iter().copied().max().unwrap_or_default();
+
+    let mut mandatory = Vec::new();
+    let mut removable = Vec::new();
+
+    // Every arrangement must get past each adapter, either by using it or by
+    // stepping over it. Because every adapter can be reached, if any earlier
+    // adapter can step beyond this one, there is an arrangement without it.
+    let mut furthest = 0;
+
+    for pos in 0..jolts.len() {
+        if pos > 0 && pos < jolts.len() - 1 {
+            if furthest > pos {
+                removable.push(jolts[pos]);
+            } else {
+                mandatory.push(jolts[pos]);
+            }
+        }
+
+        let reach = (pos + 1..jolts.len())
+            .take_while(|index| jolts[*index] - jolts[pos] <= max_step)
+            .filter(|index| steps.contains(&(jolts[*index] - jolts[pos])))
+            .last()
+            .unwrap_or(pos);
+        furthest = furthest.max(reach);
+    }
+
+    (mandatory, removable)
+}
+
+/// An iterator over every arrangement of adapters from the first to the last
+/// value, created by [`arrangements`].
+struct Arrangements<'a> {
+    jolts: &'a [usize],
+    steps: &'a [usize],
+    max_step: usize,
+    /// If each position can reach the last value, so dead ends are never
+    /// walked into.
+    reaches_end: Vec<bool>,
+    /// Positions of the adapters in the current arrangement.
+    path: Vec<usize>,
+    /// If the current path is complete or a dead end and the next one needs
+    /// to be found.
+    backtrack: bool,
+}
+
+impl Arrangements<'_> {
+    /// Find the first position at or after `from` that is an allowed step
+    /// from `pos` and can reach the last value.
+    fn next_from(&self, pos: usize, from: usize) -> Option<usize> {
+        (from..self.jolts.len())
+            .take_while(|index| self.jolts[*index] - self.jolts[pos] <= self.max_step)
+            .filter(|index| self.reaches_end[*index])
+            .find(|index| self.steps.contains(&(self.jolts[*index] - self.jolts[pos])))
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.jolts.len() - 1;
+
+        // Walk through arrangements depth first, always trying the closest
+        // adapter before ones further away.
+        loop {
+            if self.backtrack {
+                // Replace the last adapter with the next possibility after it,
+                // or keep backing up if there are no more possibilities.
+                let child = self.path.pop()?;
+                let parent = *self.path.last()?;
+
+                if let Some(next) = self.next_from(parent, child + 1) {
+                    self.path.push(next);
+                    self.backtrack = false;
+                }
+
+                continue;
+            }
+
+            let last = *self.path.last()?;
+            if last == end {
+                self.backtrack = true;
+                return Some(self.path.iter().map(|pos| self.jolts[*pos]).collect());
+            }
+
+            match self.next_from(last, last + 1) {
+                Some(next) => self.path.push(next),
+                None => self.backtrack = true,
+            }
+        }
+    }
+}
+
+/// Lazily enumerate every arrangement of adapters from the first to the last
+/// value, where each step between values must be one of the allowed step
+/// sizes. Items must be sorted for this to work.
+fn arrangements<'a>(jolts: &'a [usize], steps: &'a [usize]) -> Arrangements<'a> {
+    let max_step = steps.iter().copied().max().unwrap_or_default();
+
+    // Work backwards from the last value to find every position that can
+    // still reach it.
+    let mut reaches_end = vec![false; jolts.len()];
+    if let Some(last) = reaches_end.last_mut() {
+        *last = true;
+    }
+    for pos in (0..jolts.len().saturating_sub(1)).rev() {
+        reaches_end[pos] = (pos + 1..jolts.len())
+            .take_while(|index| jolts[*index] - jolts[pos] <= max_step)
+            .any(|index| reaches_end[index] && steps.contains(&(jolts[index] - jolts[pos])));
+    }
+
+    let path = if reaches_end.first() == Some(&true) {
+        vec![0]
+    } else {
+        vec![]
+    };
+
+    Arrangements {
+        jolts,
+        steps,
+        max_step,
+        reaches_end,
+        path,
+        backtrack: false,
+    }
 }
 
 /// Count the number of each size of step between values. Items must be sorted
@@ -45,55 +354,25 @@ fn count_steps(jolts: &[usize]) -> HashMap<usize, usize> {
     counts
 }
 
-/// Count the number of possible paths so that all values are 1 to 3 numbers
-/// apart.
-fn count_paths(jolts: &[usize], pos: usize, mut visited: &mut HashMap<usize, usize>) -> usize {
-    // If we're at the end, there is exactly one path to the end from here. This
-    // is the only place an actual number comes from, everything else is
-    // counting the number of times this position is reached.
-    if pos == jolts.len() - 1 {
-        log::trace!("Pos {} is the end", pos);
-        return 1;
-    }
-
-    // Check if we've already calculated the number of paths from this position.
-    // When we're starting from the same point, the number of possibilities is
-    // always the same.
-    if let Some(count) = visited.get(&pos) {
-        log::trace!("Already evaluated from pos {}: {}", pos, count);
-        return *count;
-    }
-
-    // Calculate the number of paths through children by:
-    // * Iterating through each element between the next position to the end.
-    //   We can skip the current position because we're already it.
-    // * Calculating the difference between us and the next position to check
-    //   if the difference is less than 3, making it a valid possibility.
-    // * Recursively calculating everything for each child and adding together
-    //   all of the possibilities.
-    //
-    // We only really need to iterate from `pos + 1` to
-    // `min(pos + 4, jolts.len())` but we'd still need the diff check, so it
-    // would look more complicated and have identical performance.
-    let mut paths = 0;
-    for index in pos + 1..jolts.len() {
-        let diff = jolts[index] - jolts[pos];
-
-        // Because the values are sorted, as soon as we're over a difference of
-        // 3 there are no more valid possibilities.
-        if diff > 3 {
-            log::trace!("No more possibilities from pos {} to index {}", pos, index);
-            break;
-        }
+/// Count the number of possible paths from the first to the last value, where
+/// each step between values must be one of the allowed step sizes. Items must
+/// be sorted for this to work.
+fn count_paths(jolts: &[usize], steps: &[usize]) -> Answer {
+    let max_step = steps.iter().copied().max().unwrap_or_default();
 
-        log::trace!("Pos {} to index {} has diff of {}", pos, index, diff);
-        paths += count_paths(&jolts, index, &mut visited);
-    }
+    // Treat each position as a node in a graph, with edges to the following
+    // positions that are an allowed step away. Because the values are sorted,
+    // as soon as we're over the largest step there are no more possibilities.
+    let successors = |pos: &usize| {
+        let pos = *pos;
 
-    // Record the value so we don't have to recalculate it in the future.
-    visited.insert(pos, paths);
+        (pos + 1..jolts.len())
+            .take_while(move |index| jolts[*index] - jolts[pos] <= max_step)
+            .filter(move |index| steps.contains(&(jolts[*index] - jolts[pos])))
+    };
 
-    paths
+    dag::count_paths(0, &(jolts.len() - 1), successors)
+        .expect("sorted values can never contain a cycle")
 }
 
 #[cfg(test)]
@@ -105,8 +384,124 @@ mod tests {
         advent_of_code::init();
 
         let mut input = vec![4, 232, 65];
-        fix_input(&mut input);
+        fix_input(&mut input, DEFAULT_DEVICE_OFFSET);
         assert_eq!(input, vec![0, 4, 65, 232, 235]);
+
+        let mut input = vec![4, 232, 65];
+        fix_input(&mut input, 1);
+        assert_eq!(input, vec![0, 4, 65, 232, 233]);
+    }
+
+    #[test]
+    fn test_check_chain() {
+        advent_of_code::init();
+
+        assert_eq!(check_chain(&[0, 1, 4, 6], DEFAULT_STEPS), Ok(()));
+        assert_eq!(
+            check_chain(&[0, 1, 5, 6], DEFAULT_STEPS),
+            Err(ChainError::Gap { from: 1, to: 5 })
+        );
+        assert_eq!(
+            check_chain(&[0, 1, 3, 6], &[1, 3]),
+            Err(ChainError::InvalidStep { from: 1, to: 3 })
+        );
+    }
+
+    #[test]
+    fn test_check_complete() {
+        advent_of_code::init();
+
+        assert_eq!(check_complete(&[0, 1, 4, 6], DEFAULT_STEPS), Ok(()));
+        assert_eq!(
+            check_complete(&[0, 1, 5, 6], DEFAULT_STEPS),
+            Err(ChainError::Unreachable {
+                reached: 1,
+                device: 6
+            })
+        );
+
+        // Not every adapter can be used, but 0, 1, 4, 7 is still a chain.
+        let input = &[0, 1, 2, 4, 7];
+        assert_eq!(check_complete(input, &[1, 3]), Ok(()));
+        assert_eq!(
+            check_chain(input, &[1, 3]),
+            Err(ChainError::InvalidStep { from: 2, to: 4 })
+        );
+        assert_eq!(count_paths(input, &[1, 3]), Answer::Small(1));
+        assert_eq!(
+            arrangements(input, &[1, 3]).collect::<Vec<_>>(),
+            vec![vec![0, 1, 4, 7]]
+        );
+
+        // Larger steps make larger device offsets possible.
+        let mut input = vec![3, 8];
+        fix_input(&mut input, 5);
+        assert_eq!(
+            check_complete(&input, &[1, 3]),
+            Err(ChainError::Unreachable {
+                reached: 3,
+                device: 13
+            })
+        );
+        assert_eq!(check_complete(&input, &[3, 5]), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_steps() {
+        advent_of_code::init();
+
+        assert_eq!("1,3".parse(), Ok(Steps(vec![1, 3])));
+        assert_eq!("3, 1,3".parse(), Ok(Steps(vec![1, 3])));
+        assert_eq!("1,3".parse::<Steps>().unwrap().to_string(), "1,3");
+        assert!("".parse::<Steps>().is_err());
+        assert!("1,a".parse::<Steps>().is_err());
+        assert!("0,1".parse::<Steps>().is_err());
+    }
+
+    #[test]
+    fn test_classify_adapters() {
+        advent_of_code::init();
+
+        let mut input = vec![16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+        fix_input(&mut input, DEFAULT_DEVICE_OFFSET);
+
+        let (mandatory, removable) = classify_adapters(&input, DEFAULT_STEPS);
+        assert_eq!(mandatory, vec![1, 4, 7, 10, 12, 15, 16, 19]);
+        assert_eq!(removable, vec![5, 6, 11]);
+    }
+
+    #[test]
+    fn test_arrangements() {
+        advent_of_code::init();
+
+        let input = &[0, 1, 2, 3];
+        let all: Vec<_> = arrangements(input, DEFAULT_STEPS).collect();
+        assert_eq!(
+            all,
+            vec![vec![0, 1, 2, 3], vec![0, 1, 3], vec![0, 2, 3], vec![0, 3]]
+        );
+
+        let mut input = vec![16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+        fix_input(&mut input, DEFAULT_DEVICE_OFFSET);
+        let all: Vec<_> = arrangements(&input, DEFAULT_STEPS).collect();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], input);
+        assert_eq!(all[7], vec![0, 1, 4, 7, 10, 12, 15, 16, 19, 22]);
+    }
+
+    #[test]
+    fn test_arrangements_prunes_dead_ends() {
+        advent_of_code::init();
+
+        let input = &[0, 1, 3];
+        let found = arrangements(input, &[1, 3]);
+        assert_eq!(found.reaches_end, vec![true, false, true]);
+        assert_eq!(found.collect::<Vec<_>>(), vec![vec![0, 3]]);
+
+        let input = &[0, 1, 5];
+        let found = arrangements(input, DEFAULT_STEPS);
+        assert_eq!(found.reaches_end, vec![false, false, true]);
+        assert_eq!(found.count(), 0);
     }
 
     #[test]
@@ -120,7 +515,7 @@ mod tests {
         assert_eq!(steps[&3], 2);
 
         let mut input = vec![16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
-        fix_input(&mut input);
+        fix_input(&mut input, DEFAULT_DEVICE_OFFSET);
         let steps = count_steps(&input);
         assert_eq!(steps.len(), 2);
         assert_eq!(steps[&1], 7);
@@ -133,9 +528,8 @@ mod tests {
 
         // Only one path can happen here, directly from start to end.
         let input = &[0, 1];
-        let mut visited = Default::default();
-        let paths = count_paths(input, 0, &mut visited);
-        assert_eq!(paths, 1);
+        let paths = count_paths(input, DEFAULT_STEPS);
+        assert_eq!(paths, Answer::Small(1));
 
         // 4 paths can happen here:
         // * 0 --> 1 --> 2 --> 3
@@ -150,8 +544,46 @@ mod tests {
         // * 0 --> 3
         //      3
         let input = &[0, 1, 2, 3];
-        let mut visited = Default::default();
-        let paths = count_paths(input, 0, &mut visited);
-        assert_eq!(paths, 4);
+        let paths = count_paths(input, DEFAULT_STEPS);
+        assert_eq!(paths, Answer::Small(4));
+
+        let mut input = vec![16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+        fix_input(&mut input, DEFAULT_DEVICE_OFFSET);
+        let paths = count_paths(&input, DEFAULT_STEPS);
+        assert_eq!(paths, Answer::Small(8));
+    }
+
+    #[test]
+    fn test_count_paths_steps() {
+        advent_of_code::init();
+
+        // Only the step of 1 is allowed, so there's a single path.
+        let input = &[0, 1, 2, 3];
+        let paths = count_paths(input, &[1]);
+        assert_eq!(paths, Answer::Small(1));
+
+        // Steps of 1 or 3 allow going directly or taking each value.
+        let paths = count_paths(input, &[1, 3]);
+        assert_eq!(paths, Answer::Small(2));
+
+        // No steps can reach from 0 to 4.
+        let input = &[0, 4];
+        let paths = count_paths(input, DEFAULT_STEPS);
+        assert_eq!(paths, Answer::Small(0));
+    }
+
+    #[test]
+    fn test_count_paths_large() {
+        advent_of_code::init();
+
+        // Values with a difference of 1 allow for an enormous number of paths,
+        // which must not overflow.
+        let input: Vec<usize> = (0..200).collect();
+        let paths = count_paths(&input, DEFAULT_STEPS);
+        assert_eq!(paths.to_i64(), None);
+        assert_eq!(
+            paths.to_string(),
+            "28610320653810477165032088685001500201865067503083660"
+        );
     }
 }
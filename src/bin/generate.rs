@@ -0,0 +1,27 @@
+use advent_of_code::generate;
+
+fn main() {
+    advent_of_code::init();
+
+    // The day is required, and a seed and size can optionally be provided
+    // after it.
+    let day: String = match advent_of_code::utils::arg(0) {
+        Some(day) => day,
+        None => {
+            let days: Vec<_> = generate::days().collect();
+            log::error!("A day must be provided, one of {}", days.join(", "));
+            std::process::exit(1);
+        }
+    };
+
+    let seed = advent_of_code::utils::arg(1).unwrap_or(0);
+    let size = advent_of_code::utils::arg(2).or_else(|| generate::default_size(&day));
+
+    match size.and_then(|size| generate::input(&day, seed, size)) {
+        Some(input) => print!("{}", input),
+        None => {
+            log::error!("Inputs can't be generated for {}", day);
+            std::process::exit(1);
+        }
+    }
+}
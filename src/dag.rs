@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::answer::Answer;
+
+/// Count the number of distinct paths from `start` to `end` in a directed
+/// acyclic graph, where `successors` provides the nodes directly reachable from
+/// a node.
+///
+/// Only nodes reachable from `start` are visited, each exactly once, and this
+/// is done without recursion so it works on graphs of any depth.
+///
+/// Returns an error if a cycle is reachable from `start`.
+pub fn count_paths<N, F, I>(start: N, end: &N, mut successors: F) -> Result<Answer, &'static str>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    // There's exactly one way to get somewhere you already are.
+    if start == *end {
+        return Ok(Answer::Small(1));
+    }
+
+    // Each node is either in progress, meaning it's still on the stack, or has
+    // a known count of paths to the end.
+    let mut counts: HashMap<N, Option<Answer>> = HashMap::new();
+    counts.insert(end.clone(), Some(Answer::Small(1)));
+    counts.insert(start.clone(), None);
+
+    // Manually manage the stack of nodes being evaluated, along with their
+    // remaining children and the paths counted so far.
+    let children: Vec<N> = successors(&start).into_iter().collect();
+    let mut stack = vec![(start, children.into_iter(), Answer::Small(0))];
+
+    while let Some((_node, children, _count)) = stack.last_mut() {
+        let child = match children.next() {
+            Some(child) => child,
+            // Once all children have been evaluated this node is done, so its
+            // count can be recorded and added to its parent.
+            None => {
+                let (node, _children, count) = stack.pop().unwrap();
+
+                match stack.last_mut() {
+                    Some((_parent, _children, parent_count)) => *parent_count += &count,
+                    None => return Ok(count),
+                }
+
+                counts.insert(node, Some(count));
+                continue;
+            }
+        };
+
+        match counts.get(&child) {
+            Some(Some(count)) => {
+                let (_node, _children, parent_count) = stack.last_mut().unwrap();
+                *parent_count += count;
+            }
+            Some(None) => return Err("graph contains a cycle"),
+            None => {
+                counts.insert(child.clone(), None);
+
+                let children: Vec<N> = successors(&child).into_iter().collect();
+                stack.push((child, children.into_iter(), Answer::Small(0)));
+            }
+        }
+    }
+
+    unreachable!("stack should only be empty after returning the start count")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_paths() {
+        // A diamond with an extra edge directly from start to end.
+        let edges: HashMap<char, Vec<char>> = vec![
+            ('a', vec!['b', 'c', 'd']),
+            ('b', vec!['d']),
+            ('c', vec!['d']),
+        ]
+        .into_iter()
+        .collect();
+        let successors = |node: &char| edges.get(node).cloned().unwrap_or_default();
+
+        assert_eq!(count_paths('a', &'d', successors), Ok(Answer::Small(3)));
+        assert_eq!(count_paths('b', &'d', successors), Ok(Answer::Small(1)));
+        assert_eq!(count_paths('d', &'d', successors), Ok(Answer::Small(1)));
+        assert_eq!(count_paths('d', &'a', successors), Ok(Answer::Small(0)));
+    }
+
+    #[test]
+    fn test_count_paths_cycle() {
+        let successors = |node: &u32| vec![(node + 1) % 3];
+        assert!(count_paths(0, &5, successors).is_err());
+    }
+
+    #[test]
+    fn test_count_paths_deep() {
+        // A single long chain would overflow the stack if done recursively.
+        let successors = |node: &usize| vec![node + 1];
+        assert_eq!(count_paths(0, &100_000, successors), Ok(Answer::Small(1)));
+    }
+
+    #[test]
+    fn test_count_paths_overflow() {
+        // Each node can go one or two steps forward, which means the number of
+        // paths follows the Fibonacci sequence.
+        let successors = |node: &usize| {
+            vec![node + 1, node + 2]
+                .into_iter()
+                .filter(|next| *next <= 100)
+        };
+        let count = count_paths(0, &100, successors).unwrap();
+        assert_eq!(count.to_string(), "573147844013817084101");
+    }
+}
@@ -0,0 +1,957 @@
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// A generator for a day, taking a random number generator and a size.
+type Generator = fn(&mut Rng, usize) -> String;
+
+/// Every day with a generator, the generator, and a size close to the real
+/// puzzle input.
+static GENERATORS: &[(&str, Generator, usize)] = &[
+    ("2015-01", parens, 7000),
+    ("2015-02", packages, 1000),
+    ("2015-03", directions, 8192),
+    ("2015-04", secret_key, 8),
+    ("2015-05", strings, 1000),
+    ("2015-06", light_commands, 300),
+    ("2020-01", expense_report, 200),
+    ("2020-02", passwords, 1000),
+    ("2020-03", tree_map, 323),
+    ("2020-04", passports, 250),
+    ("2020-05", boarding_passes, 800),
+    ("2020-06", customs_answers, 500),
+    ("2020-07", bag_rules, 600),
+    ("2020-08", console_program, 600),
+    ("2020-09", xmas_stream, 1000),
+    ("2020-10", adapters, 100),
+];
+
+/// A small random number generator using SplitMix64, so inputs generated from
+/// a seed are the same on every platform and every run.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next random value.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A random value within a range.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the range is empty.
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "range must not be empty");
+
+        let span = end.wrapping_sub(start) as u64;
+        match span.checked_add(1) {
+            Some(span) => start.wrapping_add((self.next_u64() % span) as i64),
+            None => self.next_u64() as i64,
+        }
+    }
+
+    /// A random value from 0 up to but not including `n`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `n` is 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "n must not be 0");
+
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Randomly decide something with a percent chance of being true.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+
+    /// Pick a random item.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are no items.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// Shuffle items in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+    }
+
+    /// A string of random characters from an alphabet.
+    pub fn string(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len).map(|_| *self.choose(alphabet) as char).collect()
+    }
+}
+
+/// Every day that inputs can be generated for.
+pub fn days() -> impl Iterator<Item = &'static str> {
+    GENERATORS.iter().map(|(day, _generator, _size)| *day)
+}
+
+/// The size of a day's real puzzle input, or close to it.
+pub fn default_size(day: &str) -> Option<usize> {
+    GENERATORS
+        .iter()
+        .find(|(name, _generator, _size)| *name == day)
+        .map(|(_name, _generator, size)| *size)
+}
+
+/// Generate a random but valid input for a day from a seed. What the size
+/// controls depends on the day, but larger sizes always make larger inputs.
+pub fn input(day: &str, seed: u64, size: usize) -> Option<String> {
+    let (_name, generator, _size) = GENERATORS
+        .iter()
+        .find(|(name, _generator, _size)| *name == day)?;
+
+    Some(generator(&mut Rng::new(seed), size))
+}
+
+/// Generate an input for a day from each seed below `seeds`, for checking a
+/// solution against many inputs.
+///
+/// # Panics
+///
+/// This will panic if there is no generator for the day.
+pub fn inputs(day: &str, seeds: u64, size: usize) -> impl Iterator<Item = String> + '_ {
+    (0..seeds).map(move |seed| {
+        input(day, seed, size).unwrap_or_else(|| panic!("no generator for {}", day))
+    })
+}
+
+static LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+static HEX: &[u8] = b"0123456789abcdef";
+static DIGITS: &[u8] = b"0123456789";
+
+/// Directions up and down floors. The directions climb for the first half and
+/// descend faster for the second half, so the basement is only reached about
+/// three quarters of the way through.
+pub fn parens(rng: &mut Rng, size: usize) -> String {
+    // Start with a short climb so the random walk can't reach the basement
+    // right away.
+    let climb = (size / 8).min(1000);
+
+    let mut floor: i64 = 0;
+    let mut basement = false;
+
+    let mut directions: String = (0..size)
+        .map(|index| {
+            let up_chance = if index < size / 2 { 60 } else { 30 };
+
+            if index < climb || rng.chance(up_chance) {
+                floor += 1;
+                '('
+            } else {
+                floor -= 1;
+                basement |= floor < 0;
+                ')'
+            }
+        })
+        .collect();
+
+    // Make sure the basement is always reached.
+    if !basement {
+        directions.push_str(&")".repeat((floor + 1) as usize));
+    }
+
+    directions
+}
+
+/// Package dimensions, one package per line.
+pub fn packages(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let dimensions: Vec<_> = (0..3).map(|_| rng.range(1..=30).to_string()).collect();
+            dimensions.join("x") + "\n"
+        })
+        .collect()
+}
+
+/// Directions to move north, south, east, and west.
+pub fn directions(rng: &mut Rng, size: usize) -> String {
+    rng.string(b"^v<>", size.max(1))
+}
+
+/// A secret key of lowercase letters.
+pub fn secret_key(rng: &mut Rng, size: usize) -> String {
+    rng.string(LOWERCASE, size.max(1))
+}
+
+/// Strings of lowercase letters, a mix of nice and naughty ones.
+pub fn strings(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let mut line = rng.string(LOWERCASE, 16).into_bytes();
+
+            // Purely random strings are almost never nice, so sometimes add
+            // vowels, double letters, and repeated pairs.
+            if rng.chance(50) {
+                for _ in 0..3 {
+                    let index = rng.below(line.len());
+                    line[index] = *rng.choose(b"aeiou");
+                }
+            }
+
+            if rng.chance(50) {
+                let index = rng.below(line.len() - 2);
+                line[index + rng.range(1..=2) as usize] = line[index];
+            }
+
+            if rng.chance(30) {
+                let index = rng.below(line.len() - 6);
+                let pair = [line[index], line[index + 1]];
+                let other = index + 2 + rng.below(line.len() - index - 3);
+                line[other..other + 2].copy_from_slice(&pair);
+            }
+
+            String::from_utf8(line).unwrap() + "\n"
+        })
+        .collect()
+}
+
+/// Commands to turn lights on, off, or toggle them in a 1000 by 1000 grid.
+pub fn light_commands(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let action = rng.choose(&["turn on", "turn off", "toggle"]);
+
+            let (x0, x1) = (rng.range(0..=999), rng.range(0..=999));
+            let (y0, y1) = (rng.range(0..=999), rng.range(0..=999));
+
+            format!(
+                "{} {},{} through {},{}\n",
+                action,
+                x0.min(x1),
+                y0.min(y1),
+                x0.max(x1),
+                y0.max(y1)
+            )
+        })
+        .collect()
+}
+
+/// An expense report with exactly one pair of entries and exactly one group of
+/// three entries summing to 2020, in a random order. There can be at most 900
+/// entries.
+pub fn expense_report(rng: &mut Rng, size: usize) -> String {
+    const TOTAL: i64 = 2020;
+
+    let size = size.clamp(5, 900);
+
+    // A pair made from a small and a large entry, and three small entries.
+    let (small, three) = loop {
+        let small = rng.range(1..=1009);
+        let x = rng.range(338..=1009);
+        let y = rng.range(338..=1009);
+        let z = TOTAL - x - y;
+
+        let mut three = [x, y, z];
+        three.sort_unstable();
+        if !(1..=1009).contains(&z)
+            || three[0] == three[1]
+            || three[1] == three[2]
+            || three.contains(&small)
+        {
+            continue;
+        }
+
+        // The small entries must not form any other group, including with
+        // the large half of the pair.
+        let smalls = [small, x, y, z];
+        let mut groups = 0;
+        for a in 0..smalls.len() {
+            for b in a + 1..smalls.len() {
+                if smalls[a] + smalls[b] == small {
+                    groups += 1;
+                }
+
+                for c in b + 1..smalls.len() {
+                    groups += (smalls[a] + smalls[b] + smalls[c] == TOTAL) as usize;
+                }
+            }
+        }
+
+        if groups == 1 {
+            break (small, three);
+        }
+    };
+
+    let mut entries = vec![small, TOTAL - small];
+    entries.extend_from_slice(&three);
+
+    // Other entries are all larger than half the total so they can never sum
+    // to it with each other, but could with the small entries.
+    let mut excluded: HashSet<i64> = entries.iter().copied().collect();
+    let smalls = [small, three[0], three[1], three[2]];
+    for (index, a) in smalls.iter().enumerate() {
+        excluded.insert(TOTAL - a);
+
+        for b in &smalls[index + 1..] {
+            excluded.insert(TOTAL - a - b);
+        }
+    }
+
+    while entries.len() < size {
+        let entry = rng.range(1011..=1999);
+
+        if excluded.insert(entry) {
+            entries.push(entry);
+        }
+    }
+
+    rng.shuffle(&mut entries);
+
+    entries.iter().map(|entry| format!("{}\n", entry)).collect()
+}
+
+/// Password entries with a policy, a mix of valid and invalid ones.
+pub fn passwords(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let letter = *rng.choose(LOWERCASE);
+            let num1 = rng.range(1..=8);
+            let num2 = num1 + rng.range(1..=8);
+
+            let len = rng.range(num2..=20) as usize;
+            let mut password = rng.string(LOWERCASE, len).into_bytes();
+
+            // Add the letter a few times so the policy sometimes matches.
+            for _ in 0..rng.range(0..=num2) {
+                let index = rng.below(len);
+                password[index] = letter;
+            }
+
+            format!(
+                "{}-{} {}: {}\n",
+                num1,
+                num2,
+                letter as char,
+                String::from_utf8(password).unwrap()
+            )
+        })
+        .collect()
+}
+
+/// A map of open squares and trees with `size` rows.
+pub fn tree_map(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let row: String = (0..31)
+                .map(|_| if rng.chance(25) { '#' } else { '.' })
+                .collect();
+            row + "\n"
+        })
+        .collect()
+}
+
+/// Passports separated by blank lines, with some fields missing or invalid.
+pub fn passports(rng: &mut Rng, size: usize) -> String {
+    let passports: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let invalid = rng.chance(25);
+
+            let mut fields = vec![
+                ("byr", year(rng, 1920..=2002, invalid)),
+                ("iyr", year(rng, 2010..=2020, invalid)),
+                ("eyr", year(rng, 2020..=2030, invalid)),
+                ("hgt", height(rng, invalid)),
+                ("hcl", hair_color(rng, invalid)),
+                ("ecl", eye_color(rng, invalid)),
+                ("pid", passport_id(rng, invalid)),
+                ("cid", rng.range(100..=350).to_string()),
+            ];
+
+            // Some passports are missing fields, which may include the
+            // optional country ID.
+            if rng.chance(20) {
+                fields.remove(rng.below(fields.len()));
+            }
+
+            rng.shuffle(&mut fields);
+
+            // Fields are separated by a mix of spaces and newlines.
+            let mut passport = String::new();
+            for (index, (key, value)) in fields.iter().enumerate() {
+                if index > 0 {
+                    passport.push(if rng.chance(30) { '\n' } else { ' ' });
+                }
+
+                passport.push_str(&format!("{}:{}", key, value));
+            }
+
+            passport
+        })
+        .collect();
+
+    passports.join("\n\n") + "\n"
+}
+
+/// Randomly make a field value invalid.
+fn maybe_invalid(rng: &mut Rng, invalid: bool) -> bool {
+    invalid && rng.chance(40)
+}
+
+/// A year within a range, or outside of it if invalid.
+fn year(rng: &mut Rng, range: RangeInclusive<i64>, invalid: bool) -> String {
+    if maybe_invalid(rng, invalid) {
+        let (start, end) = range.into_inner();
+        let year = if rng.chance(50) { start - 1 } else { end + 1 };
+        return year.to_string();
+    }
+
+    rng.range(range).to_string()
+}
+
+/// A height in centimeters or inches.
+fn height(rng: &mut Rng, invalid: bool) -> String {
+    if maybe_invalid(rng, invalid) {
+        return rng
+            .choose(&["149cm", "194cm", "58in", "77in", "170", "60ft"])
+            .to_string();
+    }
+
+    if rng.chance(50) {
+        format!("{}cm", rng.range(150..=193))
+    } else {
+        format!("{}in", rng.range(59..=76))
+    }
+}
+
+/// A hair color as a hex code.
+fn hair_color(rng: &mut Rng, invalid: bool) -> String {
+    if maybe_invalid(rng, invalid) {
+        return rng.string(HEX, 6);
+    }
+
+    format!("#{}", rng.string(HEX, 6))
+}
+
+/// An eye color from the allowed list.
+fn eye_color(rng: &mut Rng, invalid: bool) -> String {
+    if maybe_invalid(rng, invalid) {
+        return rng.choose(&["red", "pnk", "xry"]).to_string();
+    }
+
+    rng.choose(&["amb", "blu", "brn", "gry", "grn", "hzl", "oth"])
+        .to_string()
+}
+
+/// A nine digit passport ID.
+fn passport_id(rng: &mut Rng, invalid: bool) -> String {
+    if maybe_invalid(rng, invalid) {
+        let len = *rng.choose(&[8, 10]);
+        return rng.string(DIGITS, len);
+    }
+
+    rng.string(DIGITS, 9)
+}
+
+/// Boarding passes for a block of consecutive seats with exactly one missing
+/// seat that isn't at either end, in a random order.
+pub fn boarding_passes(rng: &mut Rng, size: usize) -> String {
+    // There are only 1024 seats, and the missing seat needs one on each side.
+    let size = size.clamp(2, 1023);
+
+    let first = rng.range(0..=(1023 - size) as i64) as usize;
+    let missing = first + 1 + rng.below(size - 1);
+
+    let mut seats: Vec<usize> = (first..=first + size)
+        .filter(|seat| *seat != missing)
+        .collect();
+    rng.shuffle(&mut seats);
+
+    seats
+        .iter()
+        .map(|seat| {
+            let pass: String = (0..10)
+                .rev()
+                .map(|bit| {
+                    let set = seat >> bit & 1 == 1;
+                    match (bit >= 3, set) {
+                        (true, false) => 'F',
+                        (true, true) => 'B',
+                        (false, false) => 'L',
+                        (false, true) => 'R',
+                    }
+                })
+                .collect();
+
+            pass + "\n"
+        })
+        .collect()
+}
+
+/// Groups of people separated by blank lines, with each person on a line with
+/// the questions they answered yes to.
+pub fn customs_answers(rng: &mut Rng, size: usize) -> String {
+    let groups: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let people: Vec<String> = (0..rng.range(1..=5))
+                .map(|_| {
+                    let mut letters = LOWERCASE.to_vec();
+                    rng.shuffle(&mut letters);
+                    letters.truncate(rng.range(1..=26) as usize);
+
+                    String::from_utf8(letters).unwrap()
+                })
+                .collect();
+
+            people.join("\n")
+        })
+        .collect();
+
+    groups.join("\n\n") + "\n"
+}
+
+/// Bag rules forming a directed acyclic graph which always includes a shiny
+/// gold bag with bags both inside and around it.
+pub fn bag_rules(rng: &mut Rng, size: usize) -> String {
+    static ADJECTIVES: &[&str] = &[
+        "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "muted",
+        "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+    ];
+    static COLORS: &[&str] = &[
+        "aqua", "black", "blue", "bronze", "brown", "coral", "crimson", "cyan", "gold", "gray",
+        "green", "indigo", "lavender", "lime", "magenta", "maroon", "olive", "orange", "plum",
+        "purple", "red", "salmon", "silver", "tan", "teal", "tomato", "violet", "white", "yellow",
+    ];
+
+    // Bags only contain bags from the next level down, so there can't be any
+    // cycles and the number of bags inside any bag stays reasonably small.
+    const LEVELS: usize = 6;
+
+    let mut names: Vec<String> = ADJECTIVES
+        .iter()
+        .flat_map(|adjective| {
+            COLORS
+                .iter()
+                .map(move |color| format!("{} {}", adjective, color))
+        })
+        .filter(|name| name != "shiny gold")
+        .collect();
+    rng.shuffle(&mut names);
+    names.truncate(size.clamp(LEVELS, ADJECTIVES.len() * COLORS.len() - 1) - 1);
+
+    // The shiny gold bag goes in the middle so bags can be both inside and
+    // around it.
+    let gold = names.len() / 2;
+    names.insert(gold, "shiny gold".to_string());
+
+    let level = |index: usize| index * LEVELS / names.len();
+    let gold_parent = (0..gold).find(|index| level(*index) + 1 == level(gold));
+
+    let mut rules: Vec<String> = (0..names.len())
+        .map(|index| {
+            let deeper: Vec<usize> = (index + 1..names.len())
+                .filter(|other| level(*other) == level(index) + 1)
+                .collect();
+
+            let mut children: Vec<usize> = Vec::new();
+            if !deeper.is_empty() {
+                for _ in 0..rng.range(0..=5) {
+                    let child = *rng.choose(&deeper);
+                    if !children.contains(&child) {
+                        children.push(child);
+                    }
+                }
+            }
+
+            // Make sure something always contains the shiny gold bag, and that
+            // it always contains something.
+            if Some(index) == gold_parent && !children.contains(&gold) {
+                children.push(gold);
+            }
+            if index == gold && children.is_empty() && !deeper.is_empty() {
+                children.push(deeper[0]);
+            }
+
+            let contents: Vec<String> = children
+                .iter()
+                .map(|child| {
+                    let count = rng.range(1..=5);
+                    let plural = if count == 1 { "bag" } else { "bags" };
+                    format!("{} {} {}", count, names[*child], plural)
+                })
+                .collect();
+
+            if contents.is_empty() {
+                format!("{} bags contain no other bags.\n", names[index])
+            } else {
+                format!("{} bags contain {}.\n", names[index], contents.join(", "))
+            }
+        })
+        .collect();
+
+    rng.shuffle(&mut rules);
+
+    rules.concat()
+}
+
+/// A console program that loops forever, where exactly one `jmp` or `nop`
+/// instruction can be flipped to let it finish.
+pub fn console_program(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(4);
+
+    // The broken instruction jumps back to the start of a loop made only of
+    // `acc` instructions, which can't be flipped. Everything before the loop
+    // only moves forward and can't jump past its start, so no other flip can
+    // escape the loop. Everything after the loop only moves forward, so the
+    // program always finishes once the broken instruction is fixed.
+    let broken = size / 4 + rng.below(size / 2);
+    let loop_start = broken - rng.range(1..=(broken as i64).clamp(1, 20)) as usize;
+
+    let lines: Vec<(&str, i64)> = (0..size)
+        .map(|index| {
+            if index == broken {
+                return ("jmp", loop_start as i64 - broken as i64);
+            }
+
+            if (loop_start..broken).contains(&index) {
+                return ("acc", rng.range(-50..=50));
+            }
+
+            // The furthest any instruction may jump.
+            let limit = if index < loop_start {
+                loop_start - index
+            } else {
+                size - index
+            } as i64;
+
+            match rng.below(3) {
+                0 => ("acc", rng.range(-50..=50)),
+                1 if index > broken => ("nop", rng.range(-50..=50)),
+                kind => {
+                    let instruction = if kind == 1 { "nop" } else { "jmp" };
+                    (instruction, rng.range(1..=limit.min(10)))
+                }
+            }
+        })
+        .collect();
+
+    lines
+        .iter()
+        .map(|(instruction, parameter)| format!("{} {:+}\n", instruction, parameter))
+        .collect()
+}
+
+/// A stream of numbers where every number after the preamble of 25 is the sum
+/// of two of the previous 25, except for one number that is instead the sum of
+/// a contiguous range of earlier numbers.
+pub fn xmas_stream(rng: &mut Rng, size: usize) -> String {
+    const PREAMBLE: usize = 25;
+
+    let size = size.max(PREAMBLE * 2);
+
+    let mut numbers: Vec<i64> = (1..=PREAMBLE as i64 * 2).collect();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(PREAMBLE);
+
+    let invalid_at = PREAMBLE + (size - PREAMBLE) * 2 / 3;
+
+    while numbers.len() < size {
+        let window = &numbers[numbers.len() - PREAMBLE..];
+
+        if numbers.len() == invalid_at {
+            numbers.push(invalid_number(
+                rng,
+                &numbers[..invalid_at - PREAMBLE],
+                window,
+            ));
+            continue;
+        }
+
+        // Pick two of the smaller values in the window so numbers don't grow
+        // too quickly, skipping the invalid number.
+        let mut sorted: Vec<i64> = window
+            .iter()
+            .copied()
+            .filter(|number| numbers.get(invalid_at) != Some(number))
+            .collect();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        let smaller = &sorted[..(sorted.len() / 2).max(2)];
+        let a = rng.below(smaller.len());
+        let b = (a + 1 + rng.below(smaller.len() - 1)) % smaller.len();
+
+        numbers.push(smaller[a] + smaller[b]);
+    }
+
+    numbers
+        .iter()
+        .map(|number| format!("{}\n", number))
+        .collect()
+}
+
+/// Find a sum of a contiguous range of earlier numbers that isn't the sum of
+/// any two numbers in the window.
+fn invalid_number(rng: &mut Rng, earlier: &[i64], window: &[i64]) -> i64 {
+    loop {
+        let start = rng.below(earlier.len() - 1);
+        let end = (start + rng.range(2..=17) as usize).min(earlier.len());
+        let sum: i64 = earlier[start..end].iter().sum();
+
+        let is_pair = window
+            .iter()
+            .enumerate()
+            .any(|(index, a)| window[index + 1..].iter().any(|b| a + b == sum));
+
+        if !is_pair {
+            return sum;
+        }
+    }
+}
+
+/// Joltage adapters that can all be chained together, in a random order.
+pub fn adapters(rng: &mut Rng, size: usize) -> String {
+    // Always include a difference of 1, as well as the 3 to the device.
+    let mut jolts = 1;
+    let mut adapters = vec![jolts];
+
+    while adapters.len() < size.max(1) {
+        jolts += *rng.choose(&[1, 1, 1, 2, 3, 3]);
+        adapters.push(jolts);
+    }
+
+    rng.shuffle(&mut adapters);
+
+    adapters
+        .iter()
+        .map(|adapter| format!("{}\n", adapter))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(0);
+        let values: Vec<_> = (0..3).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            values,
+            vec![
+                0xe220_a839_7b1d_cdaf,
+                0x6e78_9e6a_a1b9_65f4,
+                0x06c4_5d18_8009_454f
+            ]
+        );
+
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.range(-3..=3)));
+            assert!(rng.below(5) < 5);
+        }
+        assert_eq!(rng.range(7..=7), 7);
+        rng.range(i64::MIN..=i64::MAX);
+
+        let mut items: Vec<_> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_input() {
+        for day in days() {
+            let size = default_size(day).unwrap();
+
+            let input = input(day, 1, size).unwrap();
+            assert!(!input.is_empty(), "{}", day);
+
+            // The same seed always makes the same input, and a different seed
+            // makes a different one.
+            assert_eq!(Some(&input), super::input(day, 1, size).as_ref(), "{}", day);
+            assert_ne!(Some(&input), super::input(day, 2, size).as_ref(), "{}", day);
+
+            // Tiny sizes still make usable inputs.
+            assert!(!super::input(day, 1, 0).unwrap().is_empty(), "{}", day);
+        }
+
+        assert_eq!(input("2015-99", 1, 1), None);
+        assert_eq!(default_size("2015-99"), None);
+    }
+
+    #[test]
+    fn test_parens() {
+        for seed in 0..20 {
+            let input = parens(&mut Rng::new(seed), 1000);
+            assert!(input.len() >= 1000);
+            assert!(crate::parens::first_visit(input.as_bytes(), -1).is_some());
+        }
+    }
+
+    #[test]
+    fn test_expense_report() {
+        for seed in 0..20 {
+            let input = expense_report(&mut Rng::new(seed), 100);
+            let entries: Vec<i64> = input.lines().map(|line| line.parse().unwrap()).collect();
+            assert_eq!(entries.len(), 100);
+
+            let mut pairs = 0;
+            let mut triples = 0;
+            for (a_index, a) in entries.iter().enumerate() {
+                for (b_index, b) in entries.iter().enumerate().skip(a_index + 1) {
+                    pairs += (a + b == 2020) as usize;
+
+                    for c in &entries[b_index + 1..] {
+                        triples += (a + b + c == 2020) as usize;
+                    }
+                }
+            }
+
+            assert_eq!((pairs, triples), (1, 1), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_light_commands() {
+        let input = light_commands(&mut Rng::new(1), 100);
+        assert_eq!(input.lines().count(), 100);
+
+        for line in input.lines() {
+            let coords: Vec<usize> = line
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse().unwrap())
+                .collect();
+
+            assert!(coords.iter().all(|coord| *coord < 1000), "{}", line);
+            assert!(coords[0] <= coords[2] && coords[1] <= coords[3], "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_boarding_passes() {
+        let input = boarding_passes(&mut Rng::new(1), 1023);
+        assert_eq!(input.lines().count(), 1023);
+        assert!(input.lines().all(|line| line.len() == 10));
+    }
+
+    #[test]
+    fn test_bag_rules() {
+        for seed in 0..5 {
+            let input = bag_rules(&mut Rng::new(seed), 300);
+            assert_eq!(input.lines().count(), 300);
+
+            let mut graph = crate::graph::Graph::new();
+            for line in input.lines() {
+                let (parent, contents) = line.split_once(" bags contain ").unwrap();
+                let parent = graph.intern(parent);
+
+                for content in contents.trim_end_matches('.').split(", ") {
+                    if content == "no other bags" {
+                        continue;
+                    }
+
+                    let (count, child) = content.split_once(' ').unwrap();
+                    let child = child.trim_end_matches(" bags").trim_end_matches(" bag");
+                    let child = graph.intern(child);
+                    graph.add_edge(parent, child, count.parse().unwrap());
+                }
+            }
+
+            // There are no cycles, and the shiny gold bag is both inside and
+            // around other bags.
+            let gold = graph.try_id("shiny gold").unwrap();
+            assert!(graph.total_weight(gold).unwrap() > 0, "seed {}", seed);
+            assert!(!graph.ancestors(gold).is_empty(), "seed {}", seed);
+        }
+    }
+
+    /// Run a console program, returning if it finishes instead of looping.
+    fn finishes(program: &[(&str, i64)]) -> bool {
+        let mut visited = vec![false; program.len()];
+        let mut pos = 0;
+
+        while pos < program.len() {
+            if std::mem::replace(&mut visited[pos], true) {
+                return false;
+            }
+
+            pos = match program[pos] {
+                ("jmp", offset) => (pos as i64 + offset) as usize,
+                _ => pos + 1,
+            };
+        }
+
+        true
+    }
+
+    #[test]
+    fn test_console_program() {
+        for seed in 0..10 {
+            let input = console_program(&mut Rng::new(seed), 200);
+            let program: Vec<(&str, i64)> = input
+                .lines()
+                .map(|line| {
+                    let (instruction, parameter) = line.split_once(' ').unwrap();
+                    (instruction, parameter.parse().unwrap())
+                })
+                .collect();
+            assert_eq!(program.len(), 200);
+            assert!(!finishes(&program), "seed {}", seed);
+
+            // Exactly one instruction can be flipped to finish the program.
+            let fixes = (0..program.len())
+                .filter(|index| {
+                    let mut program = program.clone();
+                    program[*index].0 = match program[*index].0 {
+                        "jmp" => "nop",
+                        "nop" => "jmp",
+                        _ => return false,
+                    };
+
+                    finishes(&program)
+                })
+                .count();
+            assert_eq!(fixes, 1, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_xmas_stream() {
+        for seed in 0..10 {
+            let input = xmas_stream(&mut Rng::new(seed), 1000);
+            let numbers: Vec<i64> = input.lines().map(|line| line.parse().unwrap()).collect();
+
+            let invalid: Vec<_> = crate::sums::invalid_sums(numbers.iter().copied(), 25).collect();
+            assert_eq!(invalid.len(), 1, "seed {}", seed);
+            assert!(crate::sums::find_contiguous_sum(&numbers, invalid[0].1, 2).is_some());
+        }
+    }
+
+    #[test]
+    fn test_adapters() {
+        for seed in 0..10 {
+            let input = adapters(&mut Rng::new(seed), 100);
+            let mut jolts: Vec<usize> = input.lines().map(|line| line.parse().unwrap()).collect();
+            assert_eq!(jolts.len(), 100);
+
+            // Every adapter can be used in a chain starting from the outlet.
+            jolts.sort_unstable();
+            jolts.insert(0, 0);
+            assert!(jolts
+                .windows(2)
+                .all(|pair| (1..=3).contains(&(pair[1] - pair[0]))));
+        }
+    }
+}
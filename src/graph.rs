@@ -0,0 +1,648 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+
+/// An identifier for a node within a [`Graph`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+/// Something that went wrong while evaluating a graph.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphError {
+    /// No node exists with this name.
+    UnknownNode(String),
+    /// The nodes form a cycle, with the first node repeated at the end.
+    Cycle(Vec<String>),
+    /// The total weight was too large to be represented.
+    Overflow(String),
+}
+
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::UnknownNode(name) => write!(f, "unknown node {}", name),
+            GraphError::Cycle(names) => write!(f, "cycle found: {}", names.join(" -> ")),
+            GraphError::Overflow(name) => write!(f, "total weight of {} overflowed", name),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// Options for exporting a graph as text.
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    /// A node to highlight, along with its ancestors and descendants.
+    pub highlight: Option<NodeId>,
+    /// Only include this node and the nodes reachable from it.
+    pub root: Option<NodeId>,
+}
+
+/// How a node is highlighted in an export.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Highlight {
+    Target,
+    Ancestor,
+    Descendant,
+}
+
+impl Highlight {
+    /// The name used for this highlight in exports.
+    fn class(&self) -> &'static str {
+        match self {
+            Highlight::Target => "target",
+            Highlight::Ancestor => "ancestor",
+            Highlight::Descendant => "descendant",
+        }
+    }
+
+    /// The fill color used for this highlight.
+    fn color(&self) -> &'static str {
+        match self {
+            Highlight::Target => "#ffd700",
+            Highlight::Ancestor => "#add8e6",
+            Highlight::Descendant => "#90ee90",
+        }
+    }
+}
+
+/// A directed graph with weighted edges, where nodes are referred to by name.
+///
+/// Names are interned so each node is stored once and edges only hold
+/// [`NodeId`]s. Edges are tracked in both directions so both the nodes a node
+/// leads to and the nodes leading to it can be found quickly. Ancestor and
+/// descendant queries are remembered, so asking again is cheap.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    edges: Vec<Vec<(NodeId, usize)>>,
+    reverse_edges: Vec<Vec<(NodeId, usize)>>,
+    ancestors: RefCell<HashMap<NodeId, Rc<HashSet<NodeId>>>>,
+    descendants: RefCell<HashMap<NodeId, Rc<HashSet<NodeId>>>>,
+}
+
+impl Graph {
+    /// Create a new, empty graph.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The number of nodes in the graph.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// If the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Get the ID for a name, adding it as a new node if it doesn't exist yet.
+    pub fn intern(&mut self, name: &str) -> NodeId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+
+        let id = NodeId(self.names.len());
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.edges.push(Vec::new());
+        self.reverse_edges.push(Vec::new());
+
+        id
+    }
+
+    /// Get the ID for an existing node.
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Get the ID for an existing node, or an error naming the missing node.
+    pub fn try_id(&self, name: &str) -> Result<NodeId, GraphError> {
+        self.id(name)
+            .ok_or_else(|| GraphError::UnknownNode(name.to_string()))
+    }
+
+    /// Get the name of a node.
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id.0]
+    }
+
+    /// Iterate through the IDs of every node.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.names.len()).map(NodeId)
+    }
+
+    /// Add a weighted edge between two nodes.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: usize) {
+        self.edges[from.0].push((to, weight));
+        self.reverse_edges[to.0].push((from, weight));
+
+        // Any previous answers may have changed.
+        self.ancestors.borrow_mut().clear();
+        self.descendants.borrow_mut().clear();
+    }
+
+    /// The nodes this node has edges to, with the weight of each edge.
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, usize)] {
+        &self.edges[id.0]
+    }
+
+    /// The nodes with edges to this node, with the weight of each edge.
+    pub fn reverse_edges(&self, id: NodeId) -> &[(NodeId, usize)] {
+        &self.reverse_edges[id.0]
+    }
+
+    /// Every node that can reach this node by following edges.
+    pub fn ancestors(&self, id: NodeId) -> Rc<HashSet<NodeId>> {
+        Self::reachable(&self.ancestors, &self.reverse_edges, id)
+    }
+
+    /// Every node that can be reached from this node by following edges.
+    pub fn descendants(&self, id: NodeId) -> Rc<HashSet<NodeId>> {
+        Self::reachable(&self.descendants, &self.edges, id)
+    }
+
+    /// Find every node reachable from a starting node over a set of edges,
+    /// remembering the answer in a cache.
+    fn reachable(
+        cache: &RefCell<HashMap<NodeId, Rc<HashSet<NodeId>>>>,
+        edges: &[Vec<(NodeId, usize)>],
+        id: NodeId,
+    ) -> Rc<HashSet<NodeId>> {
+        if let Some(found) = cache.borrow().get(&id) {
+            log::trace!("Already found reachable nodes from {:?}", id);
+            return found.clone();
+        }
+
+        // A breadth first search, only including the starting node if it can
+        // be reached again through a cycle.
+        let mut found = HashSet::new();
+        let mut queue: VecDeque<NodeId> = edges[id.0].iter().map(|(to, _)| *to).collect();
+
+        while let Some(next) = queue.pop_front() {
+            if found.insert(next) {
+                queue.extend(edges[next.0].iter().map(|(to, _)| *to));
+            }
+        }
+
+        let found = Rc::new(found);
+        cache.borrow_mut().insert(id, found.clone());
+
+        found
+    }
+
+    /// Find a cycle anywhere in the graph, returned as the nodes along it with
+    /// the first node repeated at the end.
+    pub fn find_cycle(&self) -> Option<Vec<NodeId>> {
+        // Nodes that are fully evaluated and known to not be part of a cycle.
+        let mut done = vec![false; self.len()];
+
+        // A depth first search that keeps the current path on the stack, along
+        // with the next edge to look at for each node. Nodes are taken off the
+        // path as they're popped, so both are empty again after each search and
+        // can be reused.
+        let mut on_path = vec![false; self.len()];
+        let mut stack = Vec::new();
+
+        for start in self.nodes() {
+            if done[start.0] {
+                continue;
+            }
+
+            stack.push((start, 0));
+            on_path[start.0] = true;
+
+            while let Some((node, edge)) = stack.last_mut() {
+                let node = *node;
+
+                let next = match self.edges[node.0].get(*edge) {
+                    Some((next, _)) => *next,
+                    None => {
+                        done[node.0] = true;
+                        on_path[node.0] = false;
+                        stack.pop();
+                        continue;
+                    }
+                };
+                *edge += 1;
+
+                if on_path[next.0] {
+                    let from = stack.iter().position(|(node, _)| *node == next).unwrap();
+                    let mut cycle: Vec<_> = stack[from..].iter().map(|(node, _)| *node).collect();
+                    cycle.push(next);
+
+                    return Some(cycle);
+                }
+
+                if !done[next.0] {
+                    on_path[next.0] = true;
+                    stack.push((next, 0));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Ensure the graph has no cycles, returning an error with the nodes along
+    /// a cycle if it does.
+    pub fn check_acyclic(&self) -> Result<(), GraphError> {
+        match self.find_cycle() {
+            Some(cycle) => Err(GraphError::Cycle(
+                cycle
+                    .into_iter()
+                    .map(|id| self.name(id).to_string())
+                    .collect(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Calculate the total weight below a node, where each edge contributes its
+    /// weight plus its weight multiplied by the total below the node it leads
+    /// to. For bags, this is the total number of bags contained within a bag.
+    pub fn total_weight(&self, id: NodeId) -> Result<usize, GraphError> {
+        // Weights which have been fully evaluated. Everything reachable needs
+        // to be evaluated, so the cache isn't kept between calls.
+        let mut totals: HashMap<NodeId, usize> = HashMap::new();
+        let mut on_path = HashSet::new();
+
+        // Evaluate children before their parents without recursion, keeping
+        // track of the next edge to look at for each node.
+        let mut stack = vec![(id, 0)];
+        on_path.insert(id);
+
+        while let Some((node, edge)) = stack.last_mut() {
+            let node = *node;
+
+            match self.edges[node.0].get(*edge) {
+                Some((next, _weight)) => {
+                    *edge += 1;
+
+                    if on_path.contains(next) {
+                        let from = stack.iter().position(|(node, _)| node == next).unwrap();
+                        let mut cycle: Vec<_> = stack[from..]
+                            .iter()
+                            .map(|(node, _)| self.name(*node).to_string())
+                            .collect();
+                        cycle.push(self.name(*next).to_string());
+
+                        return Err(GraphError::Cycle(cycle));
+                    }
+
+                    if !totals.contains_key(next) {
+                        on_path.insert(*next);
+                        stack.push((*next, 0));
+                    }
+                }
+                None => {
+                    let total = self.edges[node.0]
+                        .iter()
+                        .try_fold(0usize, |acc, (next, weight)| {
+                            let below = weight.checked_mul(totals[next])?;
+                            acc.checked_add(*weight)?.checked_add(below)
+                        })
+                        .ok_or_else(|| GraphError::Overflow(self.name(node).to_string()))?;
+                    log::trace!("Node {} has total weight {}", self.name(node), total);
+
+                    totals.insert(node, total);
+                    on_path.remove(&node);
+                    stack.pop();
+                }
+            }
+        }
+
+        Ok(totals[&id])
+    }
+
+    /// The nodes to include in an export, sorted by name.
+    fn export_nodes(&self, options: &ExportOptions) -> Vec<NodeId> {
+        let mut nodes: Vec<_> = match options.root {
+            Some(root) => {
+                let mut nodes: Vec<_> = self.descendants(root).iter().copied().collect();
+                if !nodes.contains(&root) {
+                    nodes.push(root);
+                }
+                nodes
+            }
+            None => self.nodes().collect(),
+        };
+        nodes.sort_by(|a, b| self.name(*a).cmp(self.name(*b)));
+
+        nodes
+    }
+
+    /// The edges between exported nodes, using each node's position in the
+    /// sorted export instead of its ID. IDs depend on the order nodes were
+    /// interned, so this keeps the output the same however the graph was
+    /// built.
+    fn export_edges(&self, nodes: &[NodeId]) -> Vec<(usize, usize, usize)> {
+        let positions: HashMap<NodeId, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (*id, position))
+            .collect();
+
+        let mut edges: Vec<_> = nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(from, id)| {
+                self.edges(*id)
+                    .iter()
+                    .filter_map(|(to, weight)| Some((from, *positions.get(to)?, *weight)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        edges.sort_unstable();
+
+        edges
+    }
+
+    /// Determine how a node should be highlighted in an export.
+    fn highlight(&self, id: NodeId, options: &ExportOptions) -> Option<Highlight> {
+        let target = options.highlight?;
+
+        if id == target {
+            Some(Highlight::Target)
+        } else if self.ancestors(target).contains(&id) {
+            Some(Highlight::Ancestor)
+        } else if self.descendants(target).contains(&id) {
+            Some(Highlight::Descendant)
+        } else {
+            None
+        }
+    }
+
+    /// Export the graph in the Graphviz DOT format, with edges labeled by
+    /// their weight.
+    pub fn to_dot(&self, options: &ExportOptions) -> String {
+        let nodes = self.export_nodes(options);
+        let mut dot = String::from("digraph {\n");
+
+        for (position, id) in nodes.iter().enumerate() {
+            let name = self.name(*id).replace('\\', "\\\\").replace('"', "\\\"");
+
+            match self.highlight(*id, options) {
+                Some(highlight) => dot.push_str(&format!(
+                    "    n{} [label=\"{}\", style=filled, fillcolor=\"{}\"];\n",
+                    position,
+                    name,
+                    highlight.color()
+                )),
+                None => dot.push_str(&format!("    n{} [label=\"{}\"];\n", position, name)),
+            }
+        }
+
+        for (from, to, weight) in self.export_edges(&nodes) {
+            dot.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"];\n",
+                from, to, weight
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export the graph as a Mermaid flowchart, with edges labeled by their
+    /// weight.
+    pub fn to_mermaid(&self, options: &ExportOptions) -> String {
+        let nodes = self.export_nodes(options);
+        let mut mermaid = String::from("graph TD\n");
+
+        for (position, id) in nodes.iter().enumerate() {
+            let name = self.name(*id).replace('"', "#quot;");
+            mermaid.push_str(&format!("    n{}[\"{}\"]\n", position, name));
+        }
+
+        for (from, to, weight) in self.export_edges(&nodes) {
+            mermaid.push_str(&format!("    n{} -->|{}| n{}\n", from, weight, to));
+        }
+
+        if options.highlight.is_some() {
+            for highlight in &[
+                Highlight::Target,
+                Highlight::Ancestor,
+                Highlight::Descendant,
+            ] {
+                let ids: Vec<_> = nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_position, id)| self.highlight(**id, options) == Some(*highlight))
+                    .map(|(position, _id)| format!("n{}", position))
+                    .collect();
+
+                if ids.is_empty() {
+                    continue;
+                }
+
+                mermaid.push_str(&format!(
+                    "    classDef {} fill:{}\n    class {} {}\n",
+                    highlight.class(),
+                    highlight.color(),
+                    ids.join(","),
+                    highlight.class()
+                ));
+            }
+        }
+
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a graph from a list of weighted edges.
+    fn build(edges: &[(&str, &str, usize)]) -> Graph {
+        let mut graph = Graph::new();
+
+        for (from, to, weight) in edges {
+            let from = graph.intern(from);
+            let to = graph.intern(to);
+            graph.add_edge(from, to, *weight);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn test_intern() {
+        let mut graph = Graph::new();
+        let a = graph.intern("a");
+        let b = graph.intern("b");
+
+        assert_ne!(a, b);
+        assert_eq!(graph.intern("a"), a);
+        assert_eq!(graph.len(), 2);
+        assert_eq!(graph.name(b), "b");
+        assert_eq!(graph.id("c"), None);
+        assert_eq!(
+            graph.try_id("c"),
+            Err(GraphError::UnknownNode("c".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_edges() {
+        let graph = build(&[("a", "b", 2), ("c", "b", 3)]);
+        let b = graph.id("b").unwrap();
+
+        assert_eq!(graph.edges(b), &[]);
+        assert_eq!(
+            graph.reverse_edges(b),
+            &[(graph.id("a").unwrap(), 2), (graph.id("c").unwrap(), 3)]
+        );
+    }
+
+    #[test]
+    fn test_ancestors_descendants() {
+        let graph = build(&[("a", "b", 1), ("b", "c", 1), ("d", "c", 1)]);
+        let id = |name| graph.id(name).unwrap();
+
+        let ancestors = graph.ancestors(id("c"));
+        assert_eq!(
+            *ancestors,
+            vec![id("a"), id("b"), id("d")].into_iter().collect()
+        );
+
+        let descendants = graph.descendants(id("a"));
+        assert_eq!(*descendants, vec![id("b"), id("c")].into_iter().collect());
+
+        // Asking again should give back the remembered answer.
+        assert!(Rc::ptr_eq(&ancestors, &graph.ancestors(id("c"))));
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let graph = build(&[("a", "b", 1), ("b", "c", 1), ("a", "c", 1)]);
+        assert_eq!(graph.find_cycle(), None);
+        assert_eq!(graph.check_acyclic(), Ok(()));
+
+        let graph = build(&[("a", "b", 1), ("b", "c", 1), ("c", "b", 1)]);
+        assert_eq!(
+            graph.check_acyclic(),
+            Err(GraphError::Cycle(vec![
+                "b".to_string(),
+                "c".to_string(),
+                "b".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_total_weight() {
+        let graph = build(&[("a", "b", 2), ("b", "c", 3), ("a", "c", 1)]);
+        let id = |name| graph.id(name).unwrap();
+
+        assert_eq!(graph.total_weight(id("c")), Ok(0));
+        assert_eq!(graph.total_weight(id("b")), Ok(3));
+        assert_eq!(graph.total_weight(id("a")), Ok(2 + 2 * 3 + 1));
+
+        let graph = build(&[("a", "b", 1), ("b", "a", 1)]);
+        assert!(matches!(
+            graph.total_weight(graph.id("a").unwrap()),
+            Err(GraphError::Cycle(_))
+        ));
+
+        let graph = build(&[("a", "b", usize::MAX), ("b", "c", 2)]);
+        assert_eq!(
+            graph.total_weight(graph.id("a").unwrap()),
+            Err(GraphError::Overflow("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = build(&[("a", "b", 2), ("b", "c", 3), ("d", "b", 1)]);
+        let dot = graph.to_dot(&Default::default());
+        assert_eq!(
+            dot,
+            "digraph {
+    n0 [label=\"a\"];
+    n1 [label=\"b\"];
+    n2 [label=\"c\"];
+    n3 [label=\"d\"];
+    n0 -> n1 [label=\"2\"];
+    n1 -> n2 [label=\"3\"];
+    n3 -> n1 [label=\"1\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_to_dot_options() {
+        let graph = build(&[("a", "b", 2), ("b", "c", 3), ("d", "b", 1), ("e", "d", 1)]);
+
+        let options = ExportOptions {
+            highlight: graph.id("b"),
+            root: graph.id("d"),
+        };
+        let dot = graph.to_dot(&options);
+        assert_eq!(
+            dot,
+            "digraph {
+    n0 [label=\"b\", style=filled, fillcolor=\"#ffd700\"];
+    n1 [label=\"c\", style=filled, fillcolor=\"#90ee90\"];
+    n2 [label=\"d\", style=filled, fillcolor=\"#add8e6\"];
+    n0 -> n1 [label=\"3\"];
+    n2 -> n0 [label=\"1\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_export_stable() {
+        // The same edges added in a different order intern nodes differently,
+        // but must export the same.
+        let graph = build(&[("a", "b", 2), ("b", "c", 3), ("d", "b", 1)]);
+        let reversed = build(&[("d", "b", 1), ("b", "c", 3), ("a", "b", 2)]);
+        assert_ne!(graph.id("a"), reversed.id("a"));
+
+        let options = ExportOptions {
+            highlight: graph.id("b"),
+            root: None,
+        };
+        let reversed_options = ExportOptions {
+            highlight: reversed.id("b"),
+            root: None,
+        };
+        assert_eq!(graph.to_dot(&options), reversed.to_dot(&reversed_options));
+        assert_eq!(
+            graph.to_mermaid(&options),
+            reversed.to_mermaid(&reversed_options)
+        );
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let graph = build(&[("a", "b", 2), ("b", "c", 3)]);
+
+        let options = ExportOptions {
+            highlight: graph.id("b"),
+            root: None,
+        };
+        let mermaid = graph.to_mermaid(&options);
+        assert_eq!(
+            mermaid,
+            "graph TD
+    n0[\"a\"]
+    n1[\"b\"]
+    n2[\"c\"]
+    n0 -->|2| n1
+    n1 -->|3| n2
+    classDef target fill:#ffd700
+    class n1 target
+    classDef ancestor fill:#add8e6
+    class n0 ancestor
+    classDef descendant fill:#90ee90
+    class n2 descendant
+"
+        );
+    }
+}
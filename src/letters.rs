@@ -0,0 +1,158 @@
+/// A set of the lowercase letters `a` through `z`, stored as a single `u32`
+/// where each of the lowest 26 bits is one letter.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LetterSet(u32);
+
+impl LetterSet {
+    /// The bits used by letters.
+    const MASK: u32 = (1 << 26) - 1;
+
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    /// Create a set containing every letter.
+    pub fn all() -> Self {
+        Self(Self::MASK)
+    }
+
+    /// Get the bit for a letter, if it is a lowercase letter.
+    fn bit(letter: char) -> Option<u32> {
+        if letter.is_ascii_lowercase() {
+            Some(1 << (letter as u32 - 'a' as u32))
+        } else {
+            None
+        }
+    }
+
+    /// Add a letter to the set, returning `false` if it is not a lowercase
+    /// letter.
+    pub fn insert(&mut self, letter: char) -> bool {
+        match Self::bit(letter) {
+            Some(bit) => {
+                self.0 |= bit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Determine if the set contains a letter.
+    pub fn contains(&self, letter: char) -> bool {
+        Self::bit(letter).is_some_and(|bit| self.0 & bit != 0)
+    }
+
+    /// The letters in either set.
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// The letters in both sets.
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The letters in this set but not the other.
+    pub fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// The number of letters in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// If the set has no letters.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over each letter in the set in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+        ('a'..='z').filter(move |letter| self.contains(*letter))
+    }
+}
+
+impl std::iter::FromIterator<char> for LetterSet {
+    /// Collect letters into a set, ignoring anything that isn't a lowercase
+    /// letter.
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut set = Self::new();
+
+        for letter in iter {
+            set.insert(letter);
+        }
+
+        set
+    }
+}
+
+impl std::str::FromStr for LetterSet {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = Self::new();
+
+        for letter in s.chars() {
+            if !set.insert(letter) {
+                return Err("letter sets may only contain a through z");
+            }
+        }
+
+        Ok(set)
+    }
+}
+
+impl std::fmt::Display for LetterSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for letter in self.iter() {
+            write!(f, "{}", letter)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for LetterSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LetterSet({:?})", self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_set_from_str() {
+        let set: LetterSet = "zab".parse().unwrap();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains('a'));
+        assert!(set.contains('z'));
+        assert!(!set.contains('c'));
+        assert!(!set.contains('A'));
+        assert_eq!(set.to_string(), "abz");
+
+        assert_eq!("".parse(), Ok(LetterSet::new()));
+        assert_eq!("aa".parse::<LetterSet>().unwrap().len(), 1);
+        assert!("aB".parse::<LetterSet>().is_err());
+        assert!("a b".parse::<LetterSet>().is_err());
+        assert!("é".parse::<LetterSet>().is_err());
+    }
+
+    #[test]
+    fn test_letter_set_algebra() {
+        let a: LetterSet = "abc".parse().unwrap();
+        let b: LetterSet = "bcd".parse().unwrap();
+
+        assert_eq!(a.union(b).to_string(), "abcd");
+        assert_eq!(a.intersection(b).to_string(), "bc");
+        assert_eq!(a.difference(b).to_string(), "a");
+        assert!(a.difference(a).is_empty());
+
+        assert_eq!(LetterSet::all().len(), 26);
+        assert_eq!(LetterSet::all().intersection(a), a);
+        assert_eq!("a1b".chars().collect::<LetterSet>(), "ab".parse().unwrap());
+    }
+}
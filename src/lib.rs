@@ -1,6 +1,15 @@
 use std::fs;
+use std::io::BufReader;
 use std::sync::Once;
 
+pub mod answer;
+pub mod dag;
+pub mod generate;
+pub mod graph;
+pub mod letters;
+pub mod parens;
+pub mod schema;
+pub mod sums;
 pub mod utils;
 
 static INIT: Once = Once::new();
@@ -29,3 +38,11 @@ pub fn load_input(name: &str) -> String {
 
     input
 }
+
+/// Open the input from ./inputs/{name}.txt for reading line by line without
+/// loading the entire file into memory.
+pub fn open_input(name: &str) -> BufReader<fs::File> {
+    let file = fs::File::open(format!("./inputs/{}.txt", name)).expect("can't open file");
+
+    BufReader::new(file)
+}
@@ -0,0 +1,137 @@
+/// The number of bytes counted at once. Small enough that a chunk's total can
+/// never overflow an `i32`.
+const CHUNK_SIZE: usize = 4096;
+
+/// Count how many floors a chunk of directions moves, where `(` goes up one
+/// floor, `)` goes down one floor, and anything else doesn't move.
+fn chunk_delta(chunk: &[u8]) -> i64 {
+    // Summing comparisons without any branches lets the compiler vectorize
+    // this loop.
+    let delta: i32 = chunk
+        .iter()
+        .map(|byte| (*byte == b'(') as i32 - (*byte == b')') as i32)
+        .sum();
+
+    delta as i64
+}
+
+/// Find the floor after following every direction, where `(` goes up one
+/// floor, `)` goes down one floor, and anything else doesn't move.
+pub fn final_floor(directions: &[u8]) -> i64 {
+    directions.chunks(CHUNK_SIZE).map(chunk_delta).sum()
+}
+
+/// Find the 1-indexed byte position where a floor is first reached, starting
+/// from floor 0 at position 0.
+///
+/// Each byte moves at most one floor, so whole chunks are skipped using their
+/// counted total whenever the target is further away than the chunk is long.
+/// Only chunks where the target could be reached are searched byte by byte.
+pub fn first_visit(directions: &[u8], target: i64) -> Option<usize> {
+    if target == 0 {
+        return Some(0);
+    }
+
+    let mut floor = 0;
+
+    for (index, chunk) in directions.chunks(CHUNK_SIZE).enumerate() {
+        if (target - floor).unsigned_abs() > chunk.len() as u64 {
+            floor += chunk_delta(chunk);
+            continue;
+        }
+
+        for (offset, byte) in chunk.iter().enumerate() {
+            match byte {
+                b'(' => floor += 1,
+                b')' => floor -= 1,
+                _ => continue,
+            }
+
+            if floor == target {
+                return Some(index * CHUNK_SIZE + offset + 1);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Follow each direction one at a time, returning the final floor and the
+    /// first position the target was reached.
+    fn follow(directions: &[u8], target: i64) -> (i64, Option<usize>) {
+        let mut floor = 0;
+        let mut first = if target == 0 { Some(0) } else { None };
+
+        for (index, byte) in directions.iter().enumerate() {
+            match byte {
+                b'(' => floor += 1,
+                b')' => floor -= 1,
+                _ => (),
+            }
+
+            if first.is_none() && floor == target {
+                first = Some(index + 1);
+            }
+        }
+
+        (floor, first)
+    }
+
+    #[test]
+    fn test_final_floor() {
+        assert_eq!(final_floor(b""), 0);
+        assert_eq!(final_floor(b"((()"), 2);
+        assert_eq!(final_floor(b"))"), -2);
+        assert_eq!(final_floor(b"(x)\n("), 1);
+    }
+
+    #[test]
+    fn test_first_visit() {
+        assert_eq!(first_visit(b")", -1), Some(1));
+        assert_eq!(first_visit(b"()())", -1), Some(5));
+        assert_eq!(first_visit(b"()()(()", 2), Some(6));
+        assert_eq!(first_visit(b"(((", -1), None);
+        assert_eq!(first_visit(b"", 0), Some(0));
+    }
+
+    #[test]
+    fn test_first_visit_across_chunks() {
+        // Climb high enough to skip several chunks, then come back down.
+        let mut directions = vec![b'('; CHUNK_SIZE * 3 + 10];
+        directions.extend(vec![b')'; CHUNK_SIZE * 4]);
+
+        let up = CHUNK_SIZE * 3 + 10;
+        assert_eq!(first_visit(&directions, -1), Some(up * 2 + 1));
+        assert_eq!(first_visit(&directions, up as i64), Some(up));
+        assert_eq!(first_visit(&directions, 5), Some(5));
+        assert_eq!(
+            follow(&directions, -1),
+            (final_floor(&directions), Some(up * 2 + 1))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_follow(
+            climb in 0..CHUNK_SIZE * 2,
+            moves in proptest::collection::vec(0u8..3, 0..CHUNK_SIZE * 3),
+            target in -100i64..100,
+        ) {
+            // Start with a climb so that some chunks are far enough away from
+            // the target to be skipped.
+            let mut directions = vec![b'('; climb];
+            directions.extend(moves.iter().map(|direction| b"()x"[*direction as usize]));
+            let target = target + climb as i64;
+
+            let (floor, first) = follow(&directions, target);
+
+            prop_assert_eq!(final_floor(&directions), floor);
+            prop_assert_eq!(first_visit(&directions, target), first);
+        }
+    }
+}
@@ -0,0 +1,607 @@
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+
+/// A way of checking if the value of a field is valid.
+#[derive(Clone, Debug)]
+pub enum Validator {
+    /// Any value is valid.
+    Any,
+    /// An integer within an inclusive range.
+    Range(u32, u32),
+    /// An integer followed by a unit, where each unit has its own inclusive
+    /// range.
+    UnitRange(Vec<(String, u32, u32)>),
+    /// A value matching a regular expression.
+    Regex(Regex),
+    /// A value from a set of known values.
+    OneOf(HashSet<String>),
+    /// A value starting with a prefix, where the rest of the value must pass
+    /// another validator.
+    Prefix(String, Box<Validator>),
+}
+
+impl Validator {
+    /// Check if a value is valid, returning the reason why if it isn't.
+    pub fn check(&self, value: &str) -> Result<(), Reason> {
+        match self {
+            Validator::Any => Ok(()),
+            Validator::Range(min, max) => {
+                let number = parse_number(value, *min, *max)?;
+
+                check_range(number, *min, *max)
+            }
+            Validator::UnitRange(units) => {
+                // Split the value at the first character that isn't a digit.
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+
+                if number.is_empty() {
+                    return Err(Reason::NoNumber);
+                }
+
+                if unit.is_empty() {
+                    return Err(Reason::NoUnit);
+                }
+
+                let (_unit, min, max) = units
+                    .iter()
+                    .find(|(known, _min, _max)| known == unit)
+                    .ok_or_else(|| Reason::UnknownUnit(unit.to_string()))?;
+
+                let number = parse_number(number, *min, *max)?;
+
+                check_range(number, *min, *max)
+            }
+            Validator::Regex(regex) => {
+                if regex.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(Reason::NoMatch(regex.to_string()))
+                }
+            }
+            Validator::OneOf(values) => {
+                if values.contains(value) {
+                    Ok(())
+                } else {
+                    let mut known: Vec<_> = values.iter().cloned().collect();
+                    known.sort_unstable();
+
+                    Err(Reason::NotOneOf(known))
+                }
+            }
+            Validator::Prefix(prefix, validator) => match value.strip_prefix(prefix.as_str()) {
+                Some(rest) => validator.check(rest),
+                None => Err(Reason::MissingPrefix(prefix.clone())),
+            },
+        }
+    }
+
+    /// Check if a value is valid, returning a description of why if it isn't.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        self.check(value).map_err(|reason| reason.describe(value))
+    }
+}
+
+/// Parse a number made of only ASCII digits, unlike `str::parse` which also
+/// allows a leading `+`. Leading zeros are not allowed, so a four digit year
+/// can't be padded out to more digits.
+///
+/// Numbers too large to be represented are outside of the range.
+fn parse_number(value: &str, min: u32, max: u32) -> Result<u32, Reason> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Reason::NotNumber);
+    }
+
+    if value.len() > 1 && value.starts_with('0') {
+        return Err(Reason::LeadingZero);
+    }
+
+    value.parse().map_err(|_| Reason::OutOfRange(min, max))
+}
+
+/// Validate a number by ensuring it is between a minimum and maximum value.
+fn check_range(number: u32, min: u32, max: u32) -> Result<(), Reason> {
+    if number >= min && number <= max {
+        Ok(())
+    } else {
+        Err(Reason::OutOfRange(min, max))
+    }
+}
+
+/// The reason a value was not valid.
+///
+/// Displaying a reason gives a short summary that doesn't depend on the value,
+/// so the same reason can be counted across many records. Use
+/// [`Reason::describe`] to include the value.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Reason {
+    /// The value was expected to be a number.
+    NotNumber,
+    /// The number started with a zero.
+    LeadingZero,
+    /// The number was outside of the inclusive range.
+    OutOfRange(u32, u32),
+    /// A value with units had no number before the unit.
+    NoNumber,
+    /// A value with units had no unit after the number.
+    NoUnit,
+    /// A value had a unit that wasn't allowed.
+    UnknownUnit(String),
+    /// The value did not match the regular expression.
+    NoMatch(String),
+    /// The value was not one of the known values.
+    NotOneOf(Vec<String>),
+    /// The value did not start with the required prefix.
+    MissingPrefix(String),
+    /// The field has no rule in the schema.
+    UnknownField,
+}
+
+impl Reason {
+    /// Describe why a specific value was not valid.
+    pub fn describe(&self, value: &str) -> String {
+        match self {
+            Reason::NotNumber => format!("{} is not a number", value),
+            Reason::LeadingZero => format!("{} has a leading zero", value),
+            Reason::OutOfRange(min, max) => {
+                format!("{} is not between {} and {}", value, min, max)
+            }
+            Reason::NoNumber => format!("{} has no number", value),
+            Reason::NoUnit => format!("{} has no unit", value),
+            Reason::UnknownUnit(unit) => format!("{} is not a known unit", unit),
+            Reason::NoMatch(regex) => format!("{} does not match {}", value, regex),
+            Reason::NotOneOf(known) => format!("{} is not one of {}", value, known.join(", ")),
+            Reason::MissingPrefix(prefix) => format!("missing {}", prefix),
+            Reason::UnknownField => "not a known field".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::NotNumber => write!(f, "not a number"),
+            Reason::LeadingZero => write!(f, "leading zero"),
+            Reason::OutOfRange(min, max) => write!(f, "not between {} and {}", min, max),
+            Reason::NoNumber => write!(f, "no number"),
+            Reason::NoUnit => write!(f, "no unit"),
+            Reason::UnknownUnit(_unit) => write!(f, "unknown unit"),
+            Reason::NoMatch(regex) => write!(f, "does not match {}", regex),
+            Reason::NotOneOf(_known) => write!(f, "not an allowed value"),
+            Reason::MissingPrefix(prefix) => write!(f, "missing {}", prefix),
+            Reason::UnknownField => write!(f, "unknown field"),
+        }
+    }
+}
+
+/// A field in a record with an invalid value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldFailure<'a> {
+    pub field: &'a str,
+    pub value: &'a str,
+    pub reason: Reason,
+}
+
+impl FieldFailure<'_> {
+    /// Describe why the field's value was not valid.
+    pub fn message(&self) -> String {
+        self.reason.describe(self.value)
+    }
+}
+
+impl std::fmt::Display for FieldFailure<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message())
+    }
+}
+
+impl std::str::FromStr for Validator {
+    type Err = String;
+
+    /// Parse a validator from a kind followed by its options, such as
+    /// `range 1920-2002` or `units 150-193cm 59-76in`. A `prefix` is followed
+    /// by the prefix and then another validator, such as
+    /// `prefix # regex ^[0-9a-f]{6}$`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, options) = match s.find(' ') {
+            Some(index) => (&s[..index], s[index + 1..].trim()),
+            None => (s, ""),
+        };
+
+        match kind {
+            "any" => Ok(Validator::Any),
+            "range" => {
+                let (min, max) = parse_range(options)?;
+                Ok(Validator::Range(min, max))
+            }
+            "units" => {
+                let units = options
+                    .split_whitespace()
+                    .map(|option| {
+                        let split = option
+                            .find(|c: char| c.is_alphabetic())
+                            .ok_or_else(|| format!("{} is missing a unit", option))?;
+                        let (min, max) = parse_range(&option[..split])?;
+
+                        Ok((option[split..].to_string(), min, max))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+
+                if units.is_empty() {
+                    return Err("units must have at least one unit".to_string());
+                }
+
+                Ok(Validator::UnitRange(units))
+            }
+            "regex" => {
+                let regex = Regex::new(options).map_err(|err| err.to_string())?;
+                Ok(Validator::Regex(regex))
+            }
+            "oneof" => {
+                let values: HashSet<_> = options.split_whitespace().map(str::to_string).collect();
+
+                if values.is_empty() {
+                    return Err("oneof must have at least one value".to_string());
+                }
+
+                Ok(Validator::OneOf(values))
+            }
+            "prefix" => {
+                let mut parts = options.splitn(2, ' ');
+                let prefix = parts.next().unwrap_or_default();
+                if prefix.is_empty() {
+                    return Err("prefix must have a prefix".to_string());
+                }
+
+                let validator = parts
+                    .next()
+                    .ok_or_else(|| format!("prefix {} is missing a validator", prefix))?
+                    .parse()?;
+
+                Ok(Validator::Prefix(prefix.to_string(), Box::new(validator)))
+            }
+            _ => Err(format!("unknown validator {}", kind)),
+        }
+    }
+}
+
+/// Parse an inclusive range formatted like `150-193`.
+fn parse_range(range: &str) -> Result<(u32, u32), String> {
+    let mut parts = range.splitn(2, '-');
+
+    let min = parts.next().and_then(|min| min.parse().ok());
+    let max = parts.next().and_then(|max| max.parse().ok());
+
+    match (min, max) {
+        (Some(min), Some(max)) if min <= max => Ok((min, max)),
+        _ => Err(format!("{} is not a valid range", range)),
+    }
+}
+
+/// The rules for a single field.
+#[derive(Clone, Debug)]
+pub struct FieldRule {
+    pub name: String,
+    pub required: bool,
+    pub validator: Validator,
+}
+
+/// A set of rules describing which fields a record must have and what their
+/// values must look like.
+///
+/// Schemas can be parsed from text with one field per line, containing the
+/// field name, if it is `required` or `optional`, and a validator. Blank lines
+/// and lines starting with `#` are ignored.
+///
+/// ```text
+/// byr required range 1920-2002
+/// hgt required units 150-193cm 59-76in
+/// hcl required prefix # regex ^[0-9a-f]{6}$
+/// ecl required oneof amb blu brn
+/// cid optional any
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    fields: Vec<FieldRule>,
+}
+
+impl Schema {
+    /// Create a schema from the rules for each field.
+    pub fn new(fields: Vec<FieldRule>) -> Self {
+        Self { fields }
+    }
+
+    /// Load a schema from a file.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        contents.parse()
+    }
+
+    /// The rules for every field.
+    pub fn fields(&self) -> &[FieldRule] {
+        &self.fields
+    }
+
+    /// The rules for a single field.
+    pub fn field(&self, name: &str) -> Option<&FieldRule> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Find the names of every required field that is missing from a record.
+    pub fn missing_fields(&self, record: &HashMap<&str, &str>) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|field| field.required && !record.contains_key(field.name.as_str()))
+            .map(|field| field.name.as_str())
+            .collect()
+    }
+
+    /// Find the names of every field in a record without a rule, sorted by
+    /// name.
+    pub fn unknown_fields<'a>(&self, record: &HashMap<&'a str, &'a str>) -> Vec<&'a str> {
+        let mut unknown: Vec<_> = record
+            .keys()
+            .filter(|field| self.field(field).is_none())
+            .copied()
+            .collect();
+        unknown.sort_unstable();
+
+        unknown
+    }
+
+    /// Find every field in a record with an invalid value, along with the
+    /// reason why. Fields without a rule are always invalid. Fields are
+    /// returned sorted by name.
+    pub fn invalid_fields<'a>(&self, record: &HashMap<&'a str, &'a str>) -> Vec<FieldFailure<'a>> {
+        let mut invalid: Vec<_> = record
+            .iter()
+            .filter_map(|(field, value)| {
+                let result = match self.field(field) {
+                    Some(rule) => rule.validator.check(value),
+                    None => Err(Reason::UnknownField),
+                };
+
+                log::trace!("Field {} with data {} validity: {:?}", field, value, result);
+
+                result.err().map(|reason| FieldFailure {
+                    field,
+                    value,
+                    reason,
+                })
+            })
+            .collect();
+        invalid.sort_by_key(|failure| failure.field);
+
+        invalid
+    }
+
+    /// Determine if a record contains all of the required fields.
+    pub fn has_required_fields(&self, record: &HashMap<&str, &str>) -> bool {
+        self.missing_fields(record).is_empty()
+    }
+
+    /// Determine if a record contains all of the required fields and all of
+    /// its fields are valid.
+    pub fn is_valid(&self, record: &HashMap<&str, &str>) -> bool {
+        self.has_required_fields(record) && self.invalid_fields(record).is_empty()
+    }
+}
+
+impl std::str::FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<FieldRule> = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| format!("line {}: {}", index + 1, message);
+
+            let mut parts = line.splitn(3, ' ');
+            let name = parts.next().unwrap_or_default();
+            let required = match parts.next() {
+                Some("required") => true,
+                Some("optional") => false,
+                _ => return Err(error(format!("{} must be required or optional", name))),
+            };
+            let validator = parts
+                .next()
+                .ok_or_else(|| error(format!("{} is missing a validator", name)))?
+                .parse()
+                .map_err(error)?;
+
+            if fields.iter().any(|field| field.name == name) {
+                return Err(error(format!("{} was already defined", name)));
+            }
+
+            fields.push(FieldRule {
+                name: name.to_string(),
+                required,
+                validator,
+            });
+        }
+
+        Ok(Self { fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_SCHEMA: &str = "# Some comment
+byr required range 1920-2002
+hgt required units 150-193cm 59-76in
+hcl required prefix # regex ^[0-9a-f]{6}$
+ecl required oneof amb blu
+cid optional any";
+
+    #[test]
+    fn test_validator_from_str() {
+        assert!(matches!("any".parse(), Ok(Validator::Any)));
+        assert!(matches!("range 1-3".parse(), Ok(Validator::Range(1, 3))));
+        assert!(matches!(
+            "units 1-2cm 3-4in".parse(),
+            Ok(Validator::UnitRange(units)) if units == vec![("cm".to_string(), 1, 2), ("in".to_string(), 3, 4)]
+        ));
+
+        assert!("range 3-1".parse::<Validator>().is_err());
+        assert!("range".parse::<Validator>().is_err());
+        assert!("units 1-2".parse::<Validator>().is_err());
+        assert!("regex (".parse::<Validator>().is_err());
+        assert!("oneof".parse::<Validator>().is_err());
+        assert!("prefix #".parse::<Validator>().is_err());
+        assert!("prefix # range".parse::<Validator>().is_err());
+        assert!("unknown".parse::<Validator>().is_err());
+    }
+
+    #[test]
+    fn test_validator_validate() {
+        let range: Validator = "range 10-20".parse().unwrap();
+        assert_eq!(range.validate("10"), Ok(()));
+        assert_eq!(range.validate("20"), Ok(()));
+        assert_eq!(
+            range.validate("21"),
+            Err("21 is not between 10 and 20".to_string())
+        );
+        assert_eq!(range.validate("a"), Err("a is not a number".to_string()));
+        assert_eq!(
+            range.validate("+15"),
+            Err("+15 is not a number".to_string())
+        );
+        assert_eq!(range.validate(""), Err(" is not a number".to_string()));
+        assert_eq!(
+            range.validate("015"),
+            Err("015 has a leading zero".to_string())
+        );
+        assert_eq!(
+            range.validate("99999999999"),
+            Err("99999999999 is not between 10 and 20".to_string())
+        );
+
+        let units: Validator = "units 150-193cm 59-76in".parse().unwrap();
+        assert_eq!(units.validate("150cm"), Ok(()));
+        assert_eq!(units.validate("76in"), Ok(()));
+        assert_eq!(units.validate("190"), Err("190 has no unit".to_string()));
+        assert_eq!(units.validate("cm"), Err("cm has no number".to_string()));
+        assert_eq!(
+            units.validate("+60in"),
+            Err("+60in has no number".to_string())
+        );
+        assert_eq!(units.validate("é"), Err("é has no number".to_string()));
+        assert_eq!(
+            units.validate("60é"),
+            Err("é is not a known unit".to_string())
+        );
+        assert_eq!(
+            units.validate("190ft"),
+            Err("ft is not a known unit".to_string())
+        );
+        assert_eq!(
+            units.validate("77in"),
+            Err("77in is not between 59 and 76".to_string())
+        );
+
+        let one_of: Validator = "oneof b a".parse().unwrap();
+        assert_eq!(one_of.validate("a"), Ok(()));
+        assert_eq!(
+            one_of.validate("c"),
+            Err("c is not one of a, b".to_string())
+        );
+
+        let prefix: Validator = "prefix # range 1-9".parse().unwrap();
+        assert_eq!(prefix.validate("#5"), Ok(()));
+        assert_eq!(prefix.validate("5"), Err("missing #".to_string()));
+        assert_eq!(prefix.validate("#a"), Err("#a is not a number".to_string()));
+    }
+
+    #[test]
+    fn test_reason_display() {
+        let units: Validator = "units 150-193cm 59-76in".parse().unwrap();
+
+        // Summaries shouldn't depend on the value so they can be counted.
+        let first = units.check("190").unwrap_err();
+        let second = units.check("60").unwrap_err();
+        assert_eq!(first, second);
+        assert_eq!(first.to_string(), "no unit");
+        assert_eq!(first.describe("190"), "190 has no unit");
+
+        let range: Validator = "range 10-20".parse().unwrap();
+        assert_eq!(
+            range.check("21").unwrap_err().to_string(),
+            "not between 10 and 20"
+        );
+    }
+
+    #[test]
+    fn test_schema_from_str() {
+        let schema: Schema = TEST_SCHEMA.parse().unwrap();
+        assert_eq!(schema.fields().len(), 5);
+        assert!(schema.field("byr").unwrap().required);
+        assert!(!schema.field("cid").unwrap().required);
+
+        assert_eq!(
+            "byr sometimes any".parse::<Schema>().unwrap_err(),
+            "line 1: byr must be required or optional"
+        );
+        assert_eq!(
+            "byr required".parse::<Schema>().unwrap_err(),
+            "line 1: byr is missing a validator"
+        );
+        assert_eq!(
+            "byr required any\nbyr optional any"
+                .parse::<Schema>()
+                .unwrap_err(),
+            "line 2: byr was already defined"
+        );
+    }
+
+    #[test]
+    fn test_schema_validate() {
+        let schema: Schema = TEST_SCHEMA.parse().unwrap();
+
+        let record: HashMap<_, _> = vec![
+            ("byr", "1920"),
+            ("hgt", "60in"),
+            ("hcl", "#123abc"),
+            ("ecl", "blu"),
+        ]
+        .into_iter()
+        .collect();
+        assert!(schema.is_valid(&record));
+
+        let record: HashMap<_, _> = vec![("byr", "1919"), ("hcl", "#123abz"), ("xyz", "1")]
+            .into_iter()
+            .collect();
+        assert!(!schema.is_valid(&record));
+        assert_eq!(schema.missing_fields(&record), vec!["hgt", "ecl"]);
+        assert_eq!(schema.unknown_fields(&record), vec!["xyz"]);
+        let invalid: Vec<_> = schema
+            .invalid_fields(&record)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            invalid,
+            vec![
+                "byr: 1919 is not between 1920 and 2002",
+                "hcl: #123abz does not match ^[0-9a-f]{6}$",
+                "xyz: not a known field",
+            ]
+        );
+
+        let record: HashMap<_, _> = vec![("hcl", "123abc")].into_iter().collect();
+        assert_eq!(
+            schema.invalid_fields(&record)[0].to_string(),
+            "hcl: missing #"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+
+/// A sliding window over the most recent values which can quickly determine if
+/// any two values within it sum to a target.
+///
+/// Inserting and evicting values are `O(1)` and checking for a pair is `O(n)`
+/// in the size of the window.
+#[derive(Clone, Debug)]
+pub struct WindowedTwoSum {
+    capacity: usize,
+    window: VecDeque<i64>,
+    counts: HashMap<i64, usize>,
+}
+
+impl WindowedTwoSum {
+    /// Create a new window which holds up to `capacity` values.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            window: VecDeque::with_capacity(capacity + 1),
+            counts: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// The number of values currently in the window.
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// If the window has no values.
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// If the window has as many values as it can hold.
+    pub fn is_full(&self) -> bool {
+        self.window.len() >= self.capacity
+    }
+
+    /// Add a new value to the window, evicting and returning the oldest value
+    /// if the window was already full.
+    pub fn push(&mut self, value: i64) -> Option<i64> {
+        self.window.push_back(value);
+        *self.counts.entry(value).or_default() += 1;
+
+        if self.window.len() <= self.capacity {
+            return None;
+        }
+
+        let evicted = self.window.pop_front()?;
+        log::trace!("Evicting {} from window", evicted);
+
+        // Remove the count entirely once it reaches zero so checking for
+        // membership stays accurate.
+        if let Some(count) = self.counts.get_mut(&evicted) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&evicted);
+            }
+        }
+
+        Some(evicted)
+    }
+
+    /// Find two values at different positions in the window that sum to the
+    /// target.
+    pub fn find_pair(&self, target: i64) -> Option<(i64, i64)> {
+        for (value, count) in &self.counts {
+            // If the remainder can't be represented, there's no way it could
+            // be in the window.
+            let other = match target.checked_sub(*value) {
+                Some(other) => other,
+                None => continue,
+            };
+
+            // A value may only be paired with itself if it appears more than
+            // once in the window.
+            let found = if other == *value {
+                *count > 1
+            } else {
+                self.counts.contains_key(&other)
+            };
+
+            if found {
+                log::trace!("Combination {} + {} = {}", value, other, target);
+                return Some((*value, other));
+            }
+        }
+
+        None
+    }
+
+    /// Determine if any two values at different positions in the window sum to
+    /// the target.
+    pub fn has_pair(&self, target: i64) -> bool {
+        self.find_pair(target).is_some()
+    }
+}
+
+/// An iterator over every value which cannot be made by summing two of the
+/// values before it, created by [`invalid_sums`].
+#[derive(Clone, Debug)]
+pub struct InvalidSums<I> {
+    values: I,
+    window: WindowedTwoSum,
+    index: usize,
+}
+
+impl<I: Iterator<Item = i64>> Iterator for InvalidSums<I> {
+    /// The index of the invalid value and the value itself.
+    type Item = (usize, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for value in &mut self.values {
+            let index = self.index;
+            self.index += 1;
+
+            // Values can only be invalid once there are enough previous values
+            // to compare against.
+            let invalid = self.window.is_full() && !self.window.has_pair(value);
+            self.window.push(value);
+
+            if invalid {
+                log::debug!("{} at index {} has no previous combinations", value, index);
+                return Some((index, value));
+            }
+        }
+
+        None
+    }
+}
+
+/// Find every value which cannot be made by summing any two of the `previous`
+/// values before it, along with its index.
+///
+/// Values are only consumed as needed so this works on streams of any length.
+/// The first `previous` values are never considered invalid.
+pub fn invalid_sums<I>(values: I, previous: usize) -> InvalidSums<I::IntoIter>
+where
+    I: IntoIterator<Item = i64>,
+{
+    InvalidSums {
+        values: values.into_iter(),
+        window: WindowedTwoSum::new(previous),
+        index: 0,
+    }
+}
+
+/// Find a contiguous range of at least `min_len` values that sum to a target.
+///
+/// Runs in `O(n)` time by recording the running sum at each position. If the
+/// running sum at the end of a range minus the target has been seen before,
+/// the values between those positions sum to the target. This works for any
+/// values, including negative numbers.
+///
+/// Returns the range that ends earliest, preferring the longest one if several
+/// end at the same position.
+pub fn find_contiguous_sum(values: &[i64], target: i64, min_len: usize) -> Option<Range<usize>> {
+    // Running sums for the first n values, so prefixes[0] is always 0. These
+    // are stored as i128 so summing i64 values can never overflow.
+    let mut prefixes = Vec::with_capacity(values.len() + 1);
+    prefixes.push(0i128);
+    for value in values {
+        let last = *prefixes.last().unwrap();
+        prefixes.push(last + *value as i128);
+    }
+
+    // The earliest position each running sum was seen. Positions are only
+    // added once they're far enough behind the end to make a range of at
+    // least `min_len` values.
+    let mut seen: HashMap<i128, usize> = HashMap::with_capacity(values.len());
+    let target = target as i128;
+
+    // An empty range can't meaningfully sum to anything.
+    let min_len = min_len.max(1);
+
+    for end in min_len..prefixes.len() {
+        let start = end - min_len;
+        seen.entry(prefixes[start]).or_insert(start);
+
+        if let Some(start) = seen.get(&(prefixes[end] - target)) {
+            log::debug!("Found range {}..{} that sums to {}", start, end, target);
+            return Some(*start..end);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windowed_two_sum_push() {
+        let mut window = WindowedTwoSum::new(2);
+        assert!(window.is_empty());
+
+        assert_eq!(window.push(1), None);
+        assert_eq!(window.push(2), None);
+        assert!(window.is_full());
+
+        assert_eq!(window.push(3), Some(1));
+        assert_eq!(window.len(), 2);
+    }
+
+    #[test]
+    fn test_windowed_two_sum_has_pair() {
+        let mut window = WindowedTwoSum::new(3);
+        window.push(1);
+        window.push(5);
+        window.push(5);
+
+        assert!(window.has_pair(6));
+        assert!(window.has_pair(10));
+        assert!(!window.has_pair(2));
+
+        // After evicting one of the 5s, it can no longer pair with itself.
+        window.push(-3);
+        window.push(4);
+        assert!(!window.has_pair(10));
+        assert!(window.has_pair(2));
+        assert!(!window.has_pair(6));
+    }
+
+    #[test]
+    fn test_windowed_two_sum_extremes() {
+        let mut window = WindowedTwoSum::new(2);
+        window.push(i64::MIN);
+        window.push(i64::MAX);
+
+        assert!(window.has_pair(-1));
+        assert!(!window.has_pair(i64::MAX));
+    }
+
+    #[test]
+    fn test_invalid_sums() {
+        let values = vec![1, 2, 3, 10, 13, 23, 1];
+        let invalid: Vec<_> = invalid_sums(values, 2).collect();
+        assert_eq!(invalid, vec![(3, 10), (6, 1)]);
+
+        let invalid: Vec<_> = invalid_sums(vec![1, 2], 2).collect();
+        assert!(invalid.is_empty());
+    }
+
+    #[test]
+    fn test_find_contiguous_sum() {
+        let values = &[1, 2, 3, 4, 5];
+        assert_eq!(find_contiguous_sum(values, 9, 2), Some(1..4));
+        assert_eq!(find_contiguous_sum(values, 5, 2), Some(1..3));
+        assert_eq!(find_contiguous_sum(values, 5, 1), Some(1..3));
+        assert_eq!(find_contiguous_sum(values, 4, 2), None);
+        assert_eq!(find_contiguous_sum(values, 4, 1), Some(3..4));
+    }
+
+    #[test]
+    fn test_find_contiguous_sum_negative() {
+        let values = &[5, -7, 3, 4, -2];
+        assert_eq!(find_contiguous_sum(values, 0, 2), Some(1..4));
+        assert_eq!(find_contiguous_sum(values, 2, 2), Some(3..5));
+        assert_eq!(find_contiguous_sum(values, 100, 2), None);
+    }
+
+    #[test]
+    fn test_find_contiguous_sum_empty() {
+        assert_eq!(find_contiguous_sum(&[], 0, 2), None);
+        assert_eq!(find_contiguous_sum(&[1], 1, 2), None);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 /// Decode each line from an input into a type.
 ///
 /// # Panics
@@ -11,7 +13,144 @@ where
         .lines()
         .map(|line| {
             line.parse()
-                .expect(&format!("{} cannot be parsed into desired type", line))
+                .unwrap_or_else(|_| panic!("{} cannot be parsed into desired type", line))
         })
         .collect()
 }
+
+/// Get a positional command line argument, where 0 is the first argument after
+/// the program name, and parse it into a type.
+///
+/// Returns `None` if the argument was not provided.
+///
+/// # Panics
+///
+/// This will panic if the argument cannot be parsed.
+pub fn arg<T: std::str::FromStr>(position: usize) -> Option<T>
+where
+    <T as std::str::FromStr>::Err: std::fmt::Debug,
+{
+    let arg = std::env::args().nth(position + 1)?;
+
+    Some(
+        arg.parse()
+            .unwrap_or_else(|_| panic!("argument {} cannot be parsed into desired type", arg)),
+    )
+}
+
+/// Decode each line from a reader into a type, only reading lines as they are
+/// needed. Blank lines are skipped.
+///
+/// # Panics
+///
+/// This will panic if a line cannot be read or parsed.
+pub fn decode_reader<T: std::str::FromStr, R: std::io::BufRead>(
+    reader: R,
+) -> impl Iterator<Item = T>
+where
+    <T as std::str::FromStr>::Err: std::fmt::Debug,
+{
+    reader
+        .lines()
+        .map(|line| line.expect("can't read line"))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("{} cannot be parsed into desired type", line))
+        })
+}
+
+/// An iterator over records made of consecutive non-blank lines, created by
+/// [`records`].
+#[derive(Clone, Debug)]
+pub struct Records<'a> {
+    lines: std::str::Lines<'a>,
+}
+
+impl<'a> Iterator for Records<'a> {
+    /// Each trimmed line within the record.
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Vec::new();
+
+        for line in &mut self.lines {
+            let line = line.trim();
+
+            if !line.is_empty() {
+                record.push(line);
+            } else if !record.is_empty() {
+                return Some(record);
+            }
+        }
+
+        if record.is_empty() {
+            None
+        } else {
+            Some(record)
+        }
+    }
+}
+
+/// Split an input into records separated by blank lines.
+///
+/// Lines may end with `\n` or `\r\n` and are trimmed of surrounding
+/// whitespace. Any number of blank lines may separate records, and blank lines
+/// at the start or end of the input are ignored, so records are never empty.
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        lines: input.lines(),
+    }
+}
+
+/// Parse whitespace separated `key:value` fields from each line of a record.
+///
+/// Values may contain colons, only the first one separates the key from the
+/// value. If a key appears more than once the last value is used.
+pub fn fields<'a>(record: &[&'a str]) -> Result<HashMap<&'a str, &'a str>, String> {
+    record
+        .iter()
+        .flat_map(|line| line.split_whitespace())
+        .map(|field| {
+            let mut parts = field.splitn(2, ':');
+
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if !key.is_empty() => Ok((key, value)),
+                _ => Err(format!("{} is not a key:value field", field)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let input = "\r\na b\r\nc  \r\n\r\n\r\n  \n d\n\n";
+        let parsed: Vec<_> = records(input).collect();
+        assert_eq!(parsed, vec![vec!["a b", "c"], vec!["d"]]);
+
+        assert_eq!(records("").count(), 0);
+        assert_eq!(records("\n \n").count(), 0);
+        assert_eq!(records("a").collect::<Vec<_>>(), vec![vec!["a"]]);
+    }
+
+    #[test]
+    fn test_fields() {
+        let parsed = fields(&["a:1  b:2", "c:http://x a:3"]).unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed["a"], "3");
+        assert_eq!(parsed["b"], "2");
+        assert_eq!(parsed["c"], "http://x");
+
+        assert_eq!(fields(&["a:"]).unwrap()["a"], "");
+        assert_eq!(
+            fields(&["a:1 b"]).unwrap_err(),
+            "b is not a key:value field"
+        );
+        assert!(fields(&[":1"]).is_err());
+    }
+}